@@ -9,30 +9,30 @@ pub struct CitraRunner {
 }
 
 impl Runner for CitraRunner {
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
         let mut args = vec!["-g".to_owned(), self.path.clone()];
         if self.fullscreen {
             args.insert(0, "-f".to_owned())
         }
-        Command {
+        Ok(Command {
             program: self.path_to_citra.clone(),
             args,
             envs: std::collections::HashMap::new(),
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["citra".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "citra" => Some(Command {
+            "citra" => Ok(Command {
                 program: self.path_to_citra.clone(),
                 args: vec![],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
@@ -0,0 +1,340 @@
+//! Headless command line interface, to list and launch games from scripts without opening a window.
+
+use crate::games::{Command, Game, LaunchError};
+
+const USAGE: &str = "usage:
+    game_handler                           start the graphical interface
+    game_handler list                      list every game along with its id
+    game_handler run <name-or-id> [--subcommand <subcommand>]
+                                           run a game, or one of its runner's subcommands
+    game_handler show-command <name-or-id> [--subcommand <subcommand>]
+                                           print the command that would be run
+    game_handler doctor [name-or-id]       check a game, or the whole library, for problems
+    game_handler dump-default-theme        print the default theme, to start a theme.toml from
+    game_handler wine-builds               list the wine builds found, with their version
+    game_handler delete-wineprefix <prefix> [wineserver]
+                                           stop a wineprefix and move it to the trash
+    game_handler help                      print this message";
+
+/// handles the command line arguments (program name excluded). Returns `None` when the GUI should
+/// be started instead, and the exit code of the process otherwise.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (sub, rest) = args.split_first()?;
+
+    Some(match &sub[..] {
+        "list" => list(),
+        "run" => match parse_target(rest) {
+            Ok((target, subcommand)) => run_game(&target, subcommand),
+            Err(e) => usage_error(&e),
+        },
+        "show-command" => match parse_target(rest) {
+            Ok((target, subcommand)) => show_command(&target, subcommand),
+            Err(e) => usage_error(&e),
+        },
+        "doctor" => match rest {
+            [] => doctor(None),
+            [target] => doctor(Some(target)),
+            _ => usage_error("doctor expects at most one game"),
+        },
+        #[cfg(unix)]
+        "wine-builds" => wine_builds(),
+        #[cfg(unix)]
+        "delete-wineprefix" => match rest {
+            [prefix] => delete_wineprefix(prefix, "wineserver"),
+            [prefix, wineserver] => delete_wineprefix(prefix, wineserver),
+            _ => usage_error("delete-wineprefix expects a wineprefix"),
+        },
+        "dump-default-theme" | "--dump-default-theme" => {
+            print!("{}", crate::theme::DEFAULT_THEME);
+            0
+        }
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            0
+        }
+        other => usage_error(&format!("unknown command \"{other}\"")),
+    })
+}
+
+fn usage_error(e: &str) -> i32 {
+    eprintln!("error: {e}\n\n{USAGE}");
+    2
+}
+
+/// parses `<name-or-id> [--subcommand <subcommand>]`
+fn parse_target(args: &[String]) -> Result<(String, Option<String>), String> {
+    let mut target = None;
+    let mut subcommand = None;
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        if a == "--subcommand" {
+            subcommand = Some(
+                args.next()
+                    .ok_or("--subcommand expects a value".to_owned())?
+                    .to_owned(),
+            );
+        } else if let Some(s) = a.strip_prefix("--subcommand=") {
+            subcommand = Some(s.to_owned());
+        } else if target.is_none() {
+            target = Some(a.to_owned());
+        } else {
+            return Err(format!("unexpected argument \"{a}\""));
+        }
+    }
+    Ok((
+        target.ok_or("missing game name or id".to_owned())?,
+        subcommand,
+    ))
+}
+
+fn load() -> (Vec<Game>, crate::playtime::PlaytimeDb) {
+    let mut playtime_db = crate::playtime::PlaytimeDb::load();
+    let (mut games, broken) = crate::games::load_games(&mut playtime_db);
+    for b in broken {
+        eprintln!("warning: couldn't load {} : {}", b.path.display(), b.error);
+    }
+    crate::sort::sort_games(&mut games, crate::sort::Sorts::Name, false);
+    (games, playtime_db)
+}
+
+/// finds a game by id or toml file name (with or without the `.toml` extension), then by case
+/// insensitive name
+fn find(games: &[Game], target: &str) -> Result<usize, String> {
+    if let Some(i) = games.iter().position(|g| {
+        g.id() == target
+            || g.path_to_toml.file_name().and_then(|a| a.to_str()) == Some(target)
+            || g.path_to_toml.file_stem().and_then(|a| a.to_str()) == Some(target)
+    }) {
+        return Ok(i);
+    }
+
+    let matches = games
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| g.name.to_lowercase() == target.to_lowercase())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    match matches[..] {
+        [] => Err(format!("no game matches \"{target}\"")),
+        [i] => Ok(i),
+        _ => Err(format!(
+            "several games are named \"{target}\", use an id instead : {}",
+            matches
+                .iter()
+                .map(|i| games[*i].id())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn get_command(game: &Game, subcommand: Option<String>) -> Result<Command, String> {
+    let mut cmd = if let Some(s) = subcommand {
+        game.runner.get_subcommand_command(s).map_err(|e| match e {
+            LaunchError::UnknownSubcommand(s) => format!(
+                "runner {} has no subcommand \"{s}\". Available subcommands : {}",
+                game.runner_id,
+                game.get_subcommands().join(", ")
+            ),
+            e => e.to_string(),
+        })?
+    } else {
+        game.runner.get_command().map_err(|e| e.to_string())?
+    };
+    cmd.apply_config(&game.config);
+    Ok(cmd)
+}
+
+#[cfg(unix)]
+fn delete_wineprefix(prefix: &str, wineserver: &str) -> i32 {
+    let (games, _) = load();
+    let users = crate::library::prefix_users(&games, prefix);
+    // the game asking for it being one of them
+    if users.len() > 1 {
+        eprintln!(
+            "error: \"{prefix}\" is used by {}, it was left untouched",
+            users
+                .iter()
+                .map(|g| &g.name[..])
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        return 1;
+    }
+    match crate::wine::delete_prefix(std::path::Path::new(prefix), wineserver) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {e}");
+            1
+        }
+    }
+}
+
+#[cfg(unix)]
+fn wine_builds() -> i32 {
+    let cfg = crate::config::get_default_config_with_vals(
+        &crate::DIRS.config_dir().join("settings.toml"),
+    );
+    let builds = crate::wine_builds::scan(&cfg);
+    let name_width = builds.iter().map(|b| b.name.len()).max().unwrap_or(0);
+    for b in builds {
+        println!(
+            "{:name_width$}  {:20}  {:6}  {}",
+            b.name,
+            b.version.unwrap_or("?".to_owned()),
+            if b.proton { "proton" } else { "wine" },
+            b.root.display(),
+        );
+    }
+    0
+}
+
+fn list() -> i32 {
+    let (games, _) = load();
+    let id_width = games.iter().map(|g| g.id().len()).max().unwrap_or(0);
+    let runner_width = games.iter().map(|g| g.runner_id.len()).max().unwrap_or(0);
+    for g in games.iter() {
+        println!(
+            "{:id_width$}  {:runner_width$}  {:>20}  {}",
+            g.id(),
+            g.runner_id,
+            crate::duration_to_string(g.playtime.total),
+            g.name,
+        );
+    }
+    0
+}
+
+fn show_command(target: &str, subcommand: Option<String>) -> i32 {
+    let (games, _) = load();
+    match find(&games, target).and_then(|i| get_command(&games[i], subcommand)) {
+        Ok(cmd) => {
+            println!("{cmd}");
+            0
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            1
+        }
+    }
+}
+
+fn run_game(target: &str, subcommand: Option<String>) -> i32 {
+    let (games, mut playtime_db) = load();
+    let (game, cmd) = match find(&games, target).and_then(|i| {
+        let cmd = games[i]
+            .launch_command(subcommand.clone())
+            .map_err(|e| e.to_string())?;
+        Ok((&games[i], cmd))
+    }) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return 1;
+        }
+    };
+
+    // the sleep blocker stays active as long as this is alive
+    let _no_sleep = if game.config.no_sleep_enabled {
+        match nosleep::NoSleep::new() {
+            Ok(mut ns) => {
+                ns.start(nosleep::NoSleepType::PreventUserIdleDisplaySleep)
+                    .unwrap_or_else(|e| log::error!("couldn't initiate sleep blocker : {e}"));
+                Some(ns)
+            }
+            Err(e) => {
+                log::error!("couldn't initiate sleep blocker : {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let started = std::time::SystemTime::now();
+    // subcommands (configuration tools...) don't hand over to the game
+    let rule = subcommand
+        .is_none()
+        .then(|| game.config.tracked_process.clone())
+        .flatten();
+    let (status, end) = match wait_for_game(&cmd, rule) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("error: couldn't run {cmd} : {e}");
+            return 1;
+        }
+    };
+
+    // just like in the GUI, only the game itself counts towards playtime
+    if subcommand.is_none() {
+        playtime_db.record(crate::playtime::Session {
+            id: game.id(),
+            start: started,
+            end,
+            runner: game.runner_id.clone(),
+            exit_status: status.code(),
+            legacy: false,
+        });
+    }
+    status.code().unwrap_or(1)
+}
+
+/// runs `cmd` until the game is over, which with a tracking rule may be after the command exited,
+/// like in the GUI. Returns the exit status of the command and when the game ended.
+fn wait_for_game(
+    cmd: &Command,
+    rule: Option<crate::process_tree::TrackingRule>,
+) -> std::io::Result<(std::process::ExitStatus, std::time::SystemTime)> {
+    let mut child = cmd.spawn_attached()?;
+    let mut tree = crate::process_tree::ProcessTree::new(child.id(), rule);
+    let mut exited = None;
+    loop {
+        tree.refresh();
+        if exited.is_none() {
+            exited = child
+                .try_wait()?
+                .map(|status| (status, std::time::SystemTime::now()));
+        }
+        if let Some((status, at)) = exited {
+            if tree.game_exited() {
+                return Ok((status, tree.game_end(at)));
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+}
+
+fn doctor(target: Option<&String>) -> i32 {
+    let (games, _) = load();
+    let to_check = match target {
+        Some(t) => match find(&games, t) {
+            Ok(i) => vec![&games[i]],
+            Err(e) => {
+                eprintln!("error: {e}");
+                return 1;
+            }
+        },
+        None => games.iter().collect(),
+    };
+
+    let mut errors = false;
+    for g in to_check {
+        let report = crate::doctor::check_game(g);
+        if report.is_empty() {
+            if target.is_some() {
+                println!("{} ({}) : no problem found", g.name, g.id());
+            }
+            continue;
+        }
+        println!("{} ({}) :", g.name, g.id());
+        for p in report {
+            errors |= p.severity == crate::doctor::Severity::Error;
+            println!("    {p}");
+        }
+    }
+    if errors {
+        1
+    } else {
+        0
+    }
+}
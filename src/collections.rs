@@ -0,0 +1,229 @@
+//! Groups of games shown in the sidebar : favorites, tags, collections set in the games' toml
+//! and smart collections, which are saved queries.
+//!
+//! Smart collections are set in the global settings as `name: query`, where the query is a list
+//! of clauses joined by `and`, each optionally preceded by `not`. A clause is either `favorite`,
+//! `played`, `never played`, `running`, or a comparison `<field> <op> <value>` with fields
+//! `name`, `runner`, `tag`, `collection`, `year` and `playtime` (in hours), and operators `=`,
+//! `!=`, `~` (contains), `<`, `>`, `<=` and `>=`.
+
+use std::collections::HashMap;
+
+use crate::config::CValue;
+use crate::games::Game;
+
+/// sorted so that two-characters operators are found before their prefixes
+const OPERATORS: [(&str, Op); 7] = [
+    ("!=", Op::Ne),
+    ("<=", Op::Le),
+    (">=", Op::Ge),
+    ("=", Op::Eq),
+    ("~", Op::Contains),
+    ("<", Op::Lt),
+    (">", Op::Gt),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Contains,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+impl Op {
+    fn compare<T: PartialOrd>(&self, a: T, b: T) -> bool {
+        match self {
+            Op::Eq | Op::Contains => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Gt => a > b,
+            Op::Le => a <= b,
+            Op::Ge => a >= b,
+        }
+    }
+
+    fn compare_str(&self, a: &str, b: &str) -> bool {
+        let (a, b) = (a.to_lowercase(), b.to_lowercase());
+        match self {
+            Op::Contains => a.contains(&b),
+            _ => self.compare(a, b),
+        }
+    }
+
+    /// compares every element of `list` to `value`. `!=` means that none is equal to it.
+    fn compare_list(&self, list: &[String], value: &str) -> bool {
+        match self {
+            Op::Ne => !list.iter().any(|a| Op::Eq.compare_str(a, value)),
+            _ => list.iter().any(|a| self.compare_str(a, value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Runner,
+    Tag,
+    Collection,
+    Year,
+    Playtime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Clause {
+    Not(Box<Clause>),
+    Favorite,
+    Played,
+    Running,
+    Compare(Field, Op, String),
+}
+
+impl Clause {
+    fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if let Some(rest) = s.strip_prefix("not ") {
+            return Ok(Clause::Not(Box::new(Clause::parse(rest)?)));
+        }
+        match &s.to_lowercase()[..] {
+            "favorite" | "favourite" => return Ok(Clause::Favorite),
+            "played" => return Ok(Clause::Played),
+            "never played" => return Ok(Clause::Not(Box::new(Clause::Played))),
+            "running" => return Ok(Clause::Running),
+            _ => {}
+        }
+
+        let (pos, op_str, op) = OPERATORS
+            .iter()
+            .filter_map(|(o, op)| s.find(o).map(|p| (p, *o, *op)))
+            .min_by_key(|(p, _, _)| *p)
+            .ok_or(format!("\"{s}\" is not a valid clause"))?;
+        let field = match &s[..pos].trim().to_lowercase()[..] {
+            "name" => Field::Name,
+            "runner" => Field::Runner,
+            "tag" => Field::Tag,
+            "collection" => Field::Collection,
+            "year" => Field::Year,
+            "playtime" => Field::Playtime,
+            f => return Err(format!("unknown field \"{f}\"")),
+        };
+        let value = s[pos + op_str.len()..].trim().trim_matches('"').to_owned();
+        match field {
+            Field::Year if value.parse::<isize>().is_err() => {
+                Err(format!("\"{value}\" is not a year"))
+            }
+            Field::Playtime if value.parse::<f64>().is_err() => {
+                Err(format!("\"{value}\" is not a number of hours"))
+            }
+            _ => Ok(Clause::Compare(field, op, value)),
+        }
+    }
+
+    fn matches(&self, game: &Game) -> bool {
+        match self {
+            Clause::Not(c) => !c.matches(game),
+            Clause::Favorite => game.favorite,
+            Clause::Played => !game.playtime.total.is_zero(),
+            Clause::Running => game.is_running,
+            Clause::Compare(field, op, value) => match field {
+                Field::Name => op.compare_str(&game.name, value),
+                Field::Runner => op.compare_str(&game.runner_id, value),
+                Field::Tag => op.compare_list(&game.tags, value),
+                Field::Collection => op.compare_list(&game.collections, value),
+                Field::Year => game
+                    .release_year
+                    .map(|y| op.compare(y, value.parse().unwrap_or_default()))
+                    .unwrap_or(false),
+                Field::Playtime => op.compare(
+                    game.playtime.total.as_secs_f64() / 3600.,
+                    value.parse().unwrap_or_default(),
+                ),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmartCollection {
+    pub name: String,
+    clauses: Vec<Clause>,
+}
+
+impl SmartCollection {
+    /// parses `name: query`
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (name, query) = s
+            .split_once(':')
+            .ok_or(format!("\"{s}\" should look like \"name: query\""))?;
+        Ok(Self {
+            name: name.trim().to_owned(),
+            clauses: query
+                .split(" and ")
+                .map(Clause::parse)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+
+    pub fn matches(&self, game: &Game) -> bool {
+        self.clauses.iter().all(|a| a.matches(game))
+    }
+}
+
+/// the smart collections set in the global settings. Invalid ones are logged and skipped.
+pub fn smart_collections(cfg: &HashMap<String, (String, CValue)>) -> Vec<SmartCollection> {
+    match cfg.get("launcher:smart_collections") {
+        Some((_, CValue::StrArr(a))) => a
+            .iter()
+            .filter(|a| !a.trim().is_empty())
+            .filter_map(|a| {
+                SmartCollection::parse(a)
+                    .map_err(|e| log::error!("invalid smart collection : {e}"))
+                    .ok()
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// an entry of the sidebar
+#[derive(Debug, Clone, PartialEq)]
+pub enum Group {
+    Favorites,
+    /// the only entry showing hidden games
+    Hidden,
+    Tag(String),
+    Collection(String),
+    Smart(SmartCollection),
+}
+
+impl Group {
+    pub fn matches(&self, game: &Game) -> bool {
+        match self {
+            Group::Favorites => game.favorite,
+            Group::Hidden => game.hidden,
+            Group::Tag(t) => game.tags.contains(t),
+            Group::Collection(c) => game.collections.contains(c),
+            Group::Smart(s) => s.matches(game),
+        }
+    }
+}
+
+/// every tag and collection used by `games`, sorted and deduplicated
+pub fn tags_and_collections(games: &[Game]) -> (Vec<String>, Vec<String>) {
+    let mut tags = games
+        .iter()
+        .flat_map(|g| g.tags.iter().cloned())
+        .collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+    let mut collections = games
+        .iter()
+        .flat_map(|g| g.collections.iter().cloned())
+        .collect::<Vec<_>>();
+    collections.sort();
+    collections.dedup();
+    (tags, collections)
+}
@@ -4,6 +4,7 @@ use std::{collections::HashMap, str::FromStr};
 use crate::wine::WineRunner;
 use crate::{
     citra::CitraRunner,
+    custom::CustomRunner,
     duckstation::DuckStationRunner,
     games::{DummyRunner, Runner},
     mame::MameRunner,
@@ -28,8 +29,8 @@ pub enum CValue {
 }
 
 impl CValue {
-    fn from_toml_value(v: &toml::Value) -> Self {
-        match v {
+    fn from_toml_value(v: &toml::Value) -> Result<Self, String> {
+        Ok(match v {
             toml::Value::String(s) => Self::Str(s.to_owned()),
             toml::Value::Integer(i) => Self::Str(format!("{i}")),
             toml::Value::Float(f) => Self::Str(format!("{f}")),
@@ -46,11 +47,8 @@ impl CValue {
                 }
                 Self::StrArr(out)
             }
-            toml::Value::Table(_) => {
-                log::error!("toml config contains unexpected table");
-                panic!()
-            }
-        }
+            toml::Value::Table(_) => return Err("unexpected table".to_owned()),
+        })
     }
 
     pub fn as_string(&self) -> String {
@@ -63,7 +61,7 @@ impl CValue {
         }
     }
 
-    fn as_strarr(&self) -> Vec<String> {
+    pub fn as_strarr(&self) -> Vec<String> {
         if let Self::StrArr(s) = self {
             s.to_vec()
         } else {
@@ -152,19 +150,35 @@ impl CValue {
 
 pub static CONFIG_ORDER: once_cell::sync::Lazy<Vec<(String, Vec<String>)>> =
     once_cell::sync::Lazy::new(|| {
-        vec![
+        let mut out = vec![
             (
                 "launcher:launcher".to_owned(),
-                vec!["launcher:sgdb_api_key".to_owned()],
+                vec![
+                    "launcher:sgdb_api_key".to_owned(),
+                    "launcher:kill_grace_period".to_owned(),
+                    "launcher:sort".to_owned(),
+                    "launcher:sort_descending".to_owned(),
+                    "launcher:smart_collections".to_owned(),
+                    "launcher:view".to_owned(),
+                    "launcher:cover_width".to_owned(),
+                    "launcher:theme".to_owned(),
+                    #[cfg(unix)]
+                    "launcher:wine_build_dirs".to_owned(),
+                ],
             ),
             (
                 "metadata".to_owned(),
                 vec![
+                    "id".to_owned(),
                     "name".to_owned(),
                     "box_art".to_owned(),
                     "release_year".to_owned(),
                     "path_to_game".to_owned(),
                     "runner".to_owned(),
+                    "favorite".to_owned(),
+                    "hidden".to_owned(),
+                    "tags".to_owned(),
+                    "collections".to_owned(),
                 ],
             ),
             (
@@ -186,6 +200,8 @@ pub static CONFIG_ORDER: once_cell::sync::Lazy<Vec<(String, Vec<String>)>> =
                     "gamescope_params".to_owned(),
                     #[cfg(unix)]
                     "gamemode".to_owned(),
+                    "tracked_process".to_owned(),
+                    "tracked_process_value".to_owned(),
                 ],
             ),
             ("native:native".to_owned(), vec!["native:args".to_owned()]),
@@ -197,8 +213,14 @@ pub static CONFIG_ORDER: once_cell::sync::Lazy<Vec<(String, Vec<String>)>> =
             (
                 "wine:wine".to_owned(),
                 vec![
+                    "wine:wine_build".to_owned(),
                     "wine:path_to_wine".to_owned(),
+                    "wine:prefix_location".to_owned(),
                     "wine:wineprefix".to_owned(),
+                    "wine:arch".to_owned(),
+                    "wine:path_to_winetricks".to_owned(),
+                    "wine:winetricks_verbs".to_owned(),
+                    "wine:dll_overrides".to_owned(),
                     "wine:use_dxvk".to_owned(),
                     "wine:dxvk_path".to_owned(),
                     "wine:use_vkd3d".to_owned(),
@@ -266,7 +288,17 @@ pub static CONFIG_ORDER: once_cell::sync::Lazy<Vec<(String, Vec<String>)>> =
                     "umu:store".to_owned(),
                 ],
             ),
-        ]
+        ];
+        for def in crate::custom::DEFINITIONS.iter() {
+            out.push((
+                format!("{0}:{0}", def.id),
+                def.settings
+                    .iter()
+                    .map(|a| format!("{}:{}", def.id, a.key))
+                    .collect(),
+            ));
+        }
+        out
     });
 
 pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue)>> =
@@ -277,6 +309,60 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
             "launcher:sgdb_api_key".to_owned(),
             ("SteamGridDB API key".to_owned(), CValue::Str(String::new())),
         );
+        out.insert(
+            "launcher:kill_grace_period".to_owned(),
+            (
+                "seconds given to a killed game to exit before it is forced to".to_owned(),
+                CValue::Str("5".to_owned()),
+            ),
+        );
+        out.insert(
+            "launcher:sort".to_owned(),
+            (
+                "sort games by".to_owned(),
+                crate::sort::Sorts::default().to_config(),
+            ),
+        );
+        out.insert(
+            "launcher:sort_descending".to_owned(),
+            ("sort in descending order".to_owned(), CValue::Bool(false)),
+        );
+        out.insert(
+            "launcher:view".to_owned(),
+            (
+                "show the library as".to_owned(),
+                crate::views::LibraryView::default().to_config(),
+            ),
+        );
+        out.insert(
+            "launcher:cover_width".to_owned(),
+            (
+                "width of the box arts of the grid, in pixels".to_owned(),
+                CValue::Str(crate::IMAGE_WIDTH.to_string()),
+            ),
+        );
+        out.insert(
+            "launcher:theme".to_owned(),
+            (
+                "theme (colours set in theme.toml)".to_owned(),
+                crate::theme::ThemeType::default().to_config(),
+            ),
+        );
+        #[cfg(unix)]
+        out.insert(
+            "launcher:wine_build_dirs".to_owned(),
+            (
+                "folders containing wine builds (besides the ones of lutris and Steam)".to_owned(),
+                CValue::StrArr(vec![]),
+            ),
+        );
+        out.insert(
+            "launcher:smart_collections".to_owned(),
+            (
+                "smart collections (name: query)".to_owned(),
+                CValue::StrArr(vec![]),
+            ),
+        );
         out.insert(
             "name".to_owned(),
             ("name".to_owned(), CValue::Str(String::new())),
@@ -285,6 +371,10 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
             "box_art".to_owned(),
             ("box art".to_owned(), CValue::PickFile(String::new())),
         );
+        out.insert(
+            "id".to_owned(),
+            ("id".to_owned(), CValue::Str(String::new())),
+        );
         out.insert(
             "release_year".to_owned(),
             ("release year".to_owned(), CValue::Str(String::new())),
@@ -293,17 +383,27 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
             "path_to_game".to_owned(),
             ("path to game".to_owned(), CValue::PickFile(String::new())),
         );
+        out.insert(
+            "favorite".to_owned(),
+            ("favorite".to_owned(), CValue::Bool(false)),
+        );
+        out.insert(
+            "hidden".to_owned(),
+            ("hidden from the grid".to_owned(), CValue::Bool(false)),
+        );
+        out.insert(
+            "tags".to_owned(),
+            ("tags".to_owned(), CValue::StrArr(vec![])),
+        );
+        out.insert(
+            "collections".to_owned(),
+            ("collections".to_owned(), CValue::StrArr(vec![])),
+        );
         out.insert(
             "runner".to_owned(),
             (
                 "runner".to_owned(),
-                CValue::OneOff(
-                    crate::games::RUNNERS
-                        .iter()
-                        .map(|a| a.to_string())
-                        .collect(),
-                    0,
-                ),
+                CValue::OneOff(crate::games::runner_ids(), 0),
             ),
         );
         #[cfg(unix)]
@@ -361,6 +461,29 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
             "gamemode".to_owned(),
             ("gamemode".to_owned(), CValue::Bool(true)),
         );
+        out.insert(
+            "tracked_process".to_owned(),
+            (
+                "keep the game running while these processes are".to_owned(),
+                CValue::OneOff(
+                    vec![
+                        "none".to_owned(),
+                        "executable name".to_owned(),
+                        "path prefix".to_owned(),
+                        "steam appid".to_owned(),
+                    ],
+                    0,
+                ),
+            ),
+        );
+        out.insert(
+            "tracked_process_value".to_owned(),
+            (
+                "tracked executable name, path prefix or appid (empty : the game's appid)"
+                    .to_owned(),
+                CValue::Str(String::new()),
+            ),
+        );
 
         out.insert(
             "native:args".to_owned(),
@@ -384,6 +507,13 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
                 "wine:args".to_owned(),
                 ("arguments".to_owned(), CValue::StrArr(Vec::new())),
             );
+            out.insert(
+                "wine:wine_build".to_owned(),
+                (
+                    "wine build (empty to use the path below)".to_owned(),
+                    CValue::Str(String::new()),
+                ),
+            );
             out.insert(
                 "wine:path_to_wine".to_owned(),
                 (
@@ -391,6 +521,20 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
                     CValue::PickFile("wine".to_owned()),
                 ),
             );
+            out.insert(
+                "wine:prefix_location".to_owned(),
+                (
+                    "wineprefix (custom uses the path below, or ~/.wine)".to_owned(),
+                    CValue::OneOff(
+                        vec![
+                            "custom".to_owned(),
+                            "per game".to_owned(),
+                            "shared".to_owned(),
+                        ],
+                        0,
+                    ),
+                ),
+            );
             out.insert(
                 "wine:wineprefix".to_owned(),
                 (
@@ -398,6 +542,34 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
                     CValue::PickFolder("".to_owned()),
                 ),
             );
+            out.insert(
+                "wine:arch".to_owned(),
+                (
+                    "architecture of new wineprefixes".to_owned(),
+                    CValue::OneOff(vec!["win64".to_owned(), "win32".to_owned()], 0),
+                ),
+            );
+            out.insert(
+                "wine:path_to_winetricks".to_owned(),
+                (
+                    "path to winetricks".to_owned(),
+                    CValue::PickFile("winetricks".to_owned()),
+                ),
+            );
+            out.insert(
+                "wine:winetricks_verbs".to_owned(),
+                (
+                    "winetricks verbs, applied once to the wineprefix".to_owned(),
+                    CValue::StrArr(Vec::new()),
+                ),
+            );
+            out.insert(
+                "wine:dll_overrides".to_owned(),
+                (
+                    "dll overrides, on top of the ones of dxvk, vkd3d and dxvk_nvapi".to_owned(),
+                    CValue::StrArr(Vec::new()),
+                ),
+            );
             out.insert(
                 "wine:use_vkd3d".to_owned(),
                 ("enable vkd3d".to_owned(), CValue::Bool(false)),
@@ -526,6 +698,14 @@ pub static DEFAULT_CONFIG: once_cell::sync::Lazy<HashMap<String, (String, CValue
                 ("store".to_owned(), CValue::Str("".to_owned())),
             );
         }
+        for def in crate::custom::DEFINITIONS.iter() {
+            for setting in def.settings.iter() {
+                out.insert(
+                    format!("{}:{}", def.id, setting.key),
+                    (setting.label.clone(), setting.default.clone()),
+                );
+            }
+        }
 
         out
     });
@@ -542,6 +722,14 @@ pub fn get_default_config_with_vals(path: &std::path::Path) -> HashMap<String, (
     out
 }
 
+/// drops the blank entries left by the list editor
+fn non_empty(a: Vec<String>) -> Vec<String> {
+    a.into_iter()
+        .map(|a| a.trim().to_owned())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
 fn opt(s: String) -> Option<String> {
     if s.is_empty() {
         None
@@ -558,12 +746,25 @@ impl Cfg {
         let mut out = HashMap::new();
         let def = DEFAULT_CONFIG.get(&"runner".to_owned()).unwrap().clone();
         out.insert("runner".to_owned(), def.1);
+        out.insert("id".to_owned(), CValue::Str(crate::games::new_id()));
         Cfg(out)
     }
+    /// reads `path`, which is treated as empty if it can't be read or parsed
     pub fn from_toml(path: &std::path::Path) -> Self {
-        let toml = std::fs::read_to_string(path).unwrap_or_default();
-        let value = toml::Value::from_str(&toml[..]).unwrap();
-        let tot = value.as_table().unwrap();
+        Self::try_from_toml(path).unwrap_or_else(|e| {
+            if path.exists() {
+                log::error!("couldn't load {path:?} : {e}");
+            }
+            Cfg(HashMap::new())
+        })
+    }
+
+    /// reads `path`. Unknown keys and values of the wrong type are skipped, except for the
+    /// runner, which must be known.
+    pub fn try_from_toml(path: &std::path::Path) -> Result<Self, String> {
+        let toml = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let value = toml::Value::from_str(&toml[..]).map_err(|e| e.to_string())?;
+        let tot = value.as_table().ok_or("not a table".to_owned())?;
 
         let mut out = HashMap::new();
 
@@ -576,11 +777,17 @@ impl Cfg {
                 k.to_owned() + ":"
             };
 
-            for (k2, c2) in content.as_table().unwrap().iter() {
+            let content = content
+                .as_table()
+                .ok_or(format!("\"{k}\" should be a section"))?;
+            for (k2, c2) in content.iter() {
                 let name = prefix.clone() + k2;
                 if let Some(a) = default.get(&name) {
-                    if let Some(to_add) = CValue::from_toml_value(c2).try_coerce(&a.1) {
+                    let value = CValue::from_toml_value(c2).map_err(|e| format!("{name} : {e}"))?;
+                    if let Some(to_add) = value.clone().try_coerce(&a.1) {
                         out.insert(name, to_add);
+                    } else if name == "runner" {
+                        return Err(format!("unknown runner {c2}"));
                     } else {
                         log::warn!("config for {name} exists but is of invalid type")
                     }
@@ -590,7 +797,7 @@ impl Cfg {
             }
         }
 
-        Cfg(out)
+        Ok(Cfg(out))
     }
 
     pub fn to_toml(&self) -> String {
@@ -639,13 +846,13 @@ impl Cfg {
             )
             .clone()
     }
+    /// fails if the runner is unknown
     pub fn into_game(
         self,
         default: &std::path::Path,
         toml: std::path::PathBuf,
-        play_time_db: &HashMap<String, std::time::Duration>,
-        play_time_ty_db: &HashMap<String, std::time::Duration>,
-    ) -> crate::games::Game {
+        playtime_db: &crate::playtime::PlaytimeDb,
+    ) -> Result<crate::games::Game, String> {
         let default = get_default_config_with_vals(default);
         let box_art = self.get_or_default("box_art", &default).as_string();
         let path = self.get_or_default("path_to_game", &default).as_string();
@@ -654,16 +861,13 @@ impl Cfg {
             "found config for game \"{}\"",
             self.get_or_default("name", &default).as_string()
         );
-        let image = if let Ok(a) = image::io::Reader::open(box_art.clone()) {
-            a.decode()
-                .unwrap()
-                .thumbnail(crate::IMAGE_WIDTH, crate::IMAGE_HEIGHT)
-        } else {
-            image::DynamicImage::new_rgba8(crate::IMAGE_WIDTH, crate::IMAGE_HEIGHT)
-        }
-        // .resize(200, 300, FilterType::Triangle)
-        .to_rgba8();
-
+        // games get an id when loaded, only unsaved ones may lack one
+        let uuid = opt(self.get_or_default("id", &default).as_string()).unwrap_or(
+            toml.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+        );
         let runner_id = self.get_or_default("runner", &default).as_string();
         let runner = match &runner_id[..] {
             "dummy" => Box::new(DummyRunner()) as Box<dyn Runner>,
@@ -680,10 +884,23 @@ impl Cfg {
             #[cfg(unix)]
             "wine" => Box::new(WineRunner {
                 path: path.clone(),
+                wine_build: self.get_or_default("wine:wine_build", &default).as_string(),
+                build_dirs: self
+                    .get_or_default("launcher:wine_build_dirs", &default)
+                    .as_strarr(),
                 path_to_wine: self
                     .get_or_default("wine:path_to_wine", &default)
                     .as_string(),
-                wineprefix: opt(self.get_or_default("wine:wineprefix", &default).as_string()),
+                wineprefix: crate::wine::prefix_for(
+                    &self
+                        .get_or_default("wine:prefix_location", &default)
+                        .as_string(),
+                    opt(self.get_or_default("wine:wineprefix", &default).as_string()),
+                    &uuid,
+                ),
+                arch: crate::wine::Arch::from_config(
+                    &self.get_or_default("wine:arch", &default).as_string(),
+                ),
                 use_vkd3d: self.get_or_default("wine:use_vkd3d", &default).as_bool(),
                 vkd3d_path: opt(self.get_or_default("wine:vkd3d_path", &default).as_string()),
                 use_dxvk: self.get_or_default("wine:use_dxvk", &default).as_bool(),
@@ -701,6 +918,35 @@ impl Cfg {
                     .get_or_default("wine:fsr_strength", &default)
                     .as_string(),
                 args: self.get_or_default("wine:args", &default).as_strarr(),
+                path_to_winetricks: self
+                    .get_or_default("wine:path_to_winetricks", &default)
+                    .as_string(),
+                winetricks_verbs: self
+                    .get_or_default("wine:winetricks_verbs", &default)
+                    .as_strarr(),
+                // the global overrides are kept unless the game replaces them, and the environment
+                // variables replace both
+                dll_overrides: crate::dll_overrides::from_settings(
+                    &default
+                        .get("wine:dll_overrides")
+                        .map(|a| a.1.as_strarr())
+                        .unwrap_or_default(),
+                    &self
+                        .0
+                        .get("wine:dll_overrides")
+                        .map(|a| a.as_strarr())
+                        .unwrap_or_default(),
+                )
+                .into_iter()
+                .chain(crate::dll_overrides::from_env(
+                    self.get_or_default("env_variables", &default)
+                        .as_hashmap()
+                        .get("WINEDLLOVERRIDES")
+                        .map(|a| &a[..])
+                        .unwrap_or_default(),
+                    "the environment variables",
+                ))
+                .collect(),
             }) as Box<dyn Runner>,
             "rpcs3" => Box::new(Rpcs3Runner {
                 path: path.clone(),
@@ -766,17 +1012,49 @@ impl Cfg {
                 gameid: self.get_or_default("umu:gameid", &default).as_string(),
                 store: self.get_or_default("umu:store", &default).as_string(),
             }),
-            _ => panic!("unknown runner"),
+            id => {
+                if let Some(def) = crate::custom::DEFINITIONS.iter().find(|a| a.id == id) {
+                    Box::new(CustomRunner {
+                        path: path.clone(),
+                        values: def
+                            .settings
+                            .iter()
+                            .map(|a| {
+                                (
+                                    a.key.clone(),
+                                    self.get_or_default(&format!("{id}:{}", a.key), &default),
+                                )
+                            })
+                            .collect(),
+                        definition: def.clone(),
+                    })
+                } else {
+                    return Err(format!("unknown runner \"{id}\""));
+                }
+            }
         };
 
-        crate::games::Game {
+        let tracked_process = crate::process_tree::TrackingRule::from_config(
+            &self.get_or_default("tracked_process", &default).as_string(),
+            &self
+                .get_or_default("tracked_process_value", &default)
+                .as_string(),
+            &path,
+        );
+
+        Ok(crate::games::Game {
             name: self.get_or_default("name", &default).as_string(),
             box_art: opt(box_art),
             release_year: isize::from_str(
                 &self.get_or_default("release_year", &default).as_string()[..],
             )
             .ok(),
-            image,
+            // loaded in the background, see `thumbnails`
+            image: crate::thumbnails::placeholder(),
+            favorite: self.get_or_default("favorite", &default).as_bool(),
+            hidden: self.get_or_default("hidden", &default).as_bool(),
+            tags: non_empty(self.get_or_default("tags", &default).as_strarr()),
+            collections: non_empty(self.get_or_default("collections", &default).as_strarr()),
             path_to_game: path.into(),
             runner_id,
             runner,
@@ -799,9 +1077,13 @@ impl Cfg {
                     .as_strarr(),
                 #[cfg(unix)]
                 gamemode: self.get_or_default("gamemode", &default).as_bool(),
+                tracked_process,
             },
 
             bare_config: self,
+            date_added: std::fs::metadata(&toml)
+                .and_then(|a| a.created().or(a.modified()))
+                .ok(),
             path_to_toml: toml.clone(),
 
             // process_handle: None,
@@ -809,33 +1091,62 @@ impl Cfg {
             // process_reader: None,
             no_sleep: None,
 
-            time_played: *play_time_db
-                .get(
-                    &toml
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_str()
-                        .unwrap_or_default()
-                        .to_owned(),
-                )
-                .unwrap_or(&std::time::Duration::from_secs(0)),
-            time_played_this_year: *play_time_ty_db
-                .get(
-                    &toml
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_str()
-                        .unwrap_or_default()
-                        .to_owned(),
-                )
-                .unwrap_or(&std::time::Duration::from_secs(0)),
+            playtime: playtime_db.stats(&uuid),
+            uuid,
             time_started: None,
 
             is_running: false,
             cmd_to_run: None,
+            launch_error: None,
             psub_sender: None,
 
             managed_processes: Vec::new(),
-        }
+        })
+    }
+}
+
+/// sets `values` in the global settings at `path`, keeping the others
+pub fn save_settings(path: &std::path::Path, values: &[(&str, CValue)]) -> Result<(), String> {
+    let mut cfg = Cfg::from_toml(path);
+    for (k, v) in values {
+        cfg.0.insert(k.to_string(), v.clone());
+    }
+    std::fs::write(path, cfg.to_toml()).map_err(|e| e.to_string())
+}
+
+/// removes `key` from the metadata of the game toml at `path`, without going through `Cfg`, so
+/// that entries which can't be loaded can still be fixed
+pub fn remove_metadata_key(path: &std::path::Path, key: &str) -> Result<(), String> {
+    let toml = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut value = toml::Value::from_str(&toml[..]).map_err(|e| e.to_string())?;
+    if let Some(m) = value.get_mut("metadata").and_then(|a| a.as_table_mut()) {
+        m.remove(key);
+    }
+    std::fs::write(
+        path,
+        toml::to_string_pretty(&value).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// sets `values` in the metadata of the game toml at `path`, leaving the rest of it as written,
+/// including the keys `Cfg` doesn't know
+pub fn set_metadata_keys(path: &std::path::Path, values: &[(&str, String)]) -> Result<(), String> {
+    let toml = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut value = toml::Value::from_str(&toml[..]).map_err(|e| e.to_string())?;
+    let metadata = value
+        .as_table_mut()
+        .ok_or("not a table".to_owned())?
+        .entry("metadata")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .ok_or("\"metadata\" should be a section".to_owned())?;
+    for (k, v) in values {
+        metadata.insert(k.to_string(), toml::Value::String(v.clone()));
     }
+    std::fs::write(
+        path,
+        toml::to_string_pretty(&value).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| e.to_string())
 }
@@ -0,0 +1,350 @@
+//! Runners defined declaratively in toml files dropped in the `runners` config directory, such
+//! that adding an emulator doesn't require a new module.
+//!
+//! Templates (`program`, `args`, `cwd` and environment variables) can reference the path to the
+//! game with `{rom}`, any setting with `{setting}` and conditionally include some text with
+//! `{setting?text}`. An argument that only contains a list setting expands to one argument per
+//! element, and an argument that ends up empty because of a condition is dropped.
+
+use std::collections::HashMap;
+
+use crate::config::CValue;
+use crate::games::*;
+
+/// names that can't be used as runner ids because they already have a meaning in game configs
+const RESERVED_IDS: [&str; 3] = ["metadata", "general", "launcher"];
+
+#[derive(Debug, Clone)]
+pub struct RunnerSetting {
+    pub key: String,
+    pub label: String,
+    pub default: CValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct CustomSubcommand {
+    pub name: String,
+    pub program: Option<String>,
+    pub args: Vec<String>,
+    pub envs: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunnerDefinition {
+    pub id: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub envs: HashMap<String, String>,
+    pub cwd: Option<String>,
+    pub settings: Vec<RunnerSetting>,
+    pub subcommands: Vec<CustomSubcommand>,
+}
+
+/// every valid runner definition found in the config directory
+pub static DEFINITIONS: once_cell::sync::Lazy<Vec<RunnerDefinition>> =
+    once_cell::sync::Lazy::new(load_definitions);
+
+fn load_definitions() -> Vec<RunnerDefinition> {
+    let dir = crate::DIRS.config_dir().join("runners");
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(e) => {
+            log::info!("no custom runner loaded from {dir:?} : {e}");
+            return vec![];
+        }
+    };
+
+    let mut out: Vec<RunnerDefinition> = vec![];
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|a| a != "toml").unwrap_or(true) {
+            continue;
+        }
+        let def = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|a| a.parse::<toml::Table>().map_err(|e| e.to_string()))
+            .and_then(|a| {
+                parse_definition(
+                    path.file_stem().unwrap_or_default().to_str().unwrap_or(""),
+                    &a,
+                )
+            });
+        match def {
+            Ok(def) => {
+                if crate::games::RUNNERS.contains(&&def.id[..])
+                    || RESERVED_IDS.contains(&&def.id[..])
+                    || out.iter().any(|a| a.id == def.id)
+                {
+                    log::error!(
+                        "custom runner {path:?} uses already taken id \"{}\"",
+                        def.id
+                    );
+                } else {
+                    log::info!("found custom runner \"{}\"", def.id);
+                    out.push(def)
+                }
+            }
+            Err(e) => log::error!("couldn't load custom runner {path:?} : {e}"),
+        }
+    }
+    out.sort_by(|a, b| a.id.cmp(&b.id));
+    out
+}
+
+fn get_str(table: &toml::Table, key: &str) -> Result<Option<String>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(toml::Value::String(s)) => Ok(Some(s.to_owned())),
+        Some(_) => Err(format!("{key} should be a string")),
+    }
+}
+
+fn get_strarr(table: &toml::Table, key: &str) -> Result<Vec<String>, String> {
+    match table.get(key) {
+        None => Ok(vec![]),
+        Some(toml::Value::Array(a)) => a
+            .iter()
+            .map(|i| {
+                i.as_str()
+                    .map(|a| a.to_owned())
+                    .ok_or(format!("{key} should only contain strings"))
+            })
+            .collect(),
+        Some(_) => Err(format!("{key} should be an array of strings")),
+    }
+}
+
+fn get_envs(table: &toml::Table) -> Result<HashMap<String, String>, String> {
+    match table.get("env") {
+        None => Ok(HashMap::new()),
+        Some(toml::Value::Table(t)) => t
+            .iter()
+            .map(|(k, v)| {
+                v.as_str()
+                    .map(|a| (k.to_owned(), a.to_owned()))
+                    .ok_or(format!("environment variable {k} should be a string"))
+            })
+            .collect(),
+        Some(_) => Err("env should be a table".to_owned()),
+    }
+}
+
+fn get_tables<'a>(table: &'a toml::Table, key: &str) -> Result<Vec<&'a toml::Table>, String> {
+    match table.get(key) {
+        None => Ok(vec![]),
+        Some(toml::Value::Array(a)) => a
+            .iter()
+            .map(|i| {
+                i.as_table()
+                    .ok_or(format!("{key} should be an array of tables"))
+            })
+            .collect(),
+        Some(_) => Err(format!("{key} should be an array of tables")),
+    }
+}
+
+fn parse_setting(table: &toml::Table) -> Result<RunnerSetting, String> {
+    let key = get_str(table, "key")?.ok_or("setting without key")?;
+    if key == "rom" || key.contains(':') || key.contains('?') {
+        return Err(format!("invalid setting key \"{key}\""));
+    }
+    let label = get_str(table, "label")?.unwrap_or(key.clone());
+    let default = table.get("default");
+    let default = match &get_str(table, "type")?.unwrap_or("string".to_owned())[..] {
+        "string" => CValue::Str(default.and_then(|a| a.as_str()).unwrap_or("").to_owned()),
+        "file" => CValue::PickFile(default.and_then(|a| a.as_str()).unwrap_or("").to_owned()),
+        "folder" => CValue::PickFolder(default.and_then(|a| a.as_str()).unwrap_or("").to_owned()),
+        "bool" => CValue::Bool(default.and_then(|a| a.as_bool()).unwrap_or(false)),
+        "list" => CValue::StrArr(get_strarr(table, "default")?),
+        "choice" => {
+            let choices = get_strarr(table, "choices")?;
+            if choices.is_empty() {
+                return Err(format!("setting {key} has no choices"));
+            }
+            let chosen = default
+                .and_then(|a| a.as_str())
+                .and_then(|d| choices.iter().position(|c| c == d))
+                .unwrap_or(0);
+            CValue::OneOff(choices, chosen)
+        }
+        t => return Err(format!("setting {key} has unknown type \"{t}\"")),
+    };
+    Ok(RunnerSetting {
+        key,
+        label,
+        default,
+    })
+}
+
+fn parse_definition(fallback_id: &str, table: &toml::Table) -> Result<RunnerDefinition, String> {
+    let id = get_str(table, "id")?.unwrap_or(fallback_id.to_owned());
+    if id.is_empty() || id.contains(':') {
+        return Err(format!("invalid runner id \"{id}\""));
+    }
+
+    let settings = get_tables(table, "settings")?
+        .into_iter()
+        .map(parse_setting)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let subcommands = get_tables(table, "subcommands")?
+        .into_iter()
+        .map(|t| {
+            Ok(CustomSubcommand {
+                name: get_str(t, "name")?.ok_or("subcommand without name")?,
+                program: get_str(t, "program")?,
+                args: get_strarr(t, "args")?,
+                envs: get_envs(t)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(RunnerDefinition {
+        program: get_str(table, "program")?.ok_or("missing program")?,
+        args: get_strarr(table, "args")?,
+        envs: get_envs(table)?,
+        cwd: get_str(table, "cwd")?,
+        settings,
+        subcommands,
+        id,
+    })
+}
+
+/// runner built from a [`RunnerDefinition`]
+#[derive(Debug, Clone)]
+pub struct CustomRunner {
+    pub path: String,
+    pub definition: RunnerDefinition,
+    /// the values of the settings of the definition, keyed without the runner prefix
+    pub values: HashMap<String, CValue>,
+}
+
+impl CustomRunner {
+    fn value_string(&self, key: &str) -> String {
+        if key == "rom" {
+            return self.path.clone();
+        }
+        match self.values.get(key) {
+            Some(CValue::Bool(b)) => b.to_string(),
+            Some(CValue::StrArr(a)) => a.join(" "),
+            Some(v) => v.as_string(),
+            None => {
+                log::warn!(
+                    "runner {} uses unknown setting \"{key}\" in a template",
+                    self.definition.id
+                );
+                String::new()
+            }
+        }
+    }
+
+    fn is_set(&self, key: &str) -> bool {
+        if key == "rom" {
+            return !self.path.is_empty();
+        }
+        match self.values.get(key) {
+            Some(CValue::Bool(b)) => *b,
+            Some(CValue::StrArr(a)) => !a.is_empty(),
+            Some(v) => !v.as_string().is_empty(),
+            None => false,
+        }
+    }
+
+    /// expands a template into zero, one or several arguments
+    fn expand(&self, template: &str) -> Vec<String> {
+        if let Some(key) = template.strip_prefix('{').and_then(|a| a.strip_suffix('}')) {
+            if let Some(CValue::StrArr(a)) = self.values.get(key) {
+                return a.clone();
+            }
+        }
+
+        let mut out = String::new();
+        let mut conditional = false;
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            out += &rest[..start];
+            if let Some(len) = rest[start..].find('}') {
+                let inner = &rest[start + 1..start + len];
+                if let Some((key, text)) = inner.split_once('?') {
+                    conditional = true;
+                    if self.is_set(key) {
+                        out += text;
+                    }
+                } else {
+                    out += &self.value_string(inner);
+                }
+                rest = &rest[start + len + 1..];
+            } else {
+                out += &rest[start..];
+                rest = "";
+            }
+        }
+        out += rest;
+
+        if out.is_empty() && conditional {
+            vec![]
+        } else {
+            vec![out]
+        }
+    }
+
+    fn build(
+        &self,
+        program: &str,
+        args: &[String],
+        envs: &HashMap<String, String>,
+        cwd: Option<&String>,
+    ) -> Command {
+        Command {
+            program: self.expand(program).join(" "),
+            args: args.iter().flat_map(|a| self.expand(a)).collect(),
+            envs: envs
+                .iter()
+                .map(|(k, v)| (k.to_owned(), self.expand(v).join(" ")))
+                .collect(),
+            cwd: cwd
+                .map(|a| self.expand(a).join(" "))
+                .filter(|a| !a.is_empty())
+                .map(|a| a.into()),
+        }
+    }
+}
+
+impl Runner for CustomRunner {
+    fn get_command(&self) -> Result<Command, LaunchError> {
+        let def = &self.definition;
+        Ok(self.build(&def.program, &def.args, &def.envs, def.cwd.as_ref()))
+    }
+    /// only when the command references the game with `{rom}`
+    fn uses_path(&self) -> bool {
+        let def = &self.definition;
+        std::iter::once(&def.program)
+            .chain(def.args.iter())
+            .chain(def.envs.values())
+            .chain(def.cwd.iter())
+            .any(|a| a.contains("{rom}"))
+    }
+    fn get_subcommands(&self) -> Vec<String> {
+        self.definition
+            .subcommands
+            .iter()
+            .map(|a| a.name.clone())
+            .collect()
+    }
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
+        let def = &self.definition;
+        let s = def
+            .subcommands
+            .iter()
+            .find(|a| a.name == command)
+            .ok_or(LaunchError::UnknownSubcommand(command))?;
+        let mut envs = def.envs.clone();
+        envs.extend(s.envs.clone());
+        Ok(self.build(
+            s.program.as_ref().unwrap_or(&def.program),
+            &s.args,
+            &envs,
+            def.cwd.as_ref(),
+        ))
+    }
+}
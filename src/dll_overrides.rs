@@ -0,0 +1,186 @@
+//! Dll overrides set in the global settings and in the settings of games, written `dll=mode` like in
+//! `WINEDLLOVERRIDES` (e.g. `dinput8=n,b` or `winemenubuilder.exe=d`). They are merged with the
+//! ones needed by dxvk, vkd3d and dxvk_nvapi, the overrides of the game taking precedence over the
+//! global ones which take precedence over those of the components. A `WINEDLLOVERRIDES` set in
+//! the environment variables takes precedence over all of them.
+
+/// the load order of a dll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Native,
+    Builtin,
+    NativeBuiltin,
+    BuiltinNative,
+    Disabled,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 5] = [
+        Mode::Native,
+        Mode::Builtin,
+        Mode::NativeBuiltin,
+        Mode::BuiltinNative,
+        Mode::Disabled,
+    ];
+
+    /// how it is written in `WINEDLLOVERRIDES`
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Native => "n",
+            Self::Builtin => "b",
+            Self::NativeBuiltin => "n,b",
+            Self::BuiltinNative => "b,n",
+            Self::Disabled => "d",
+        }
+    }
+
+    pub fn from_code(s: &str) -> Option<Self> {
+        match &s.replace(' ', "")[..] {
+            "n" => Some(Self::Native),
+            "b" => Some(Self::Builtin),
+            "n,b" => Some(Self::NativeBuiltin),
+            "b,n" => Some(Self::BuiltinNative),
+            // an empty load order disables the dll as well
+            "d" | "" => Some(Self::Disabled),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Native => write!(f, "native"),
+            Self::Builtin => write!(f, "builtin"),
+            Self::NativeBuiltin => write!(f, "native, then builtin"),
+            Self::BuiltinNative => write!(f, "builtin, then native"),
+            Self::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub dll: String,
+    pub mode: Mode,
+    /// where it was set, e.g. `dxvk` or `the game`
+    pub source: String,
+}
+
+impl Override {
+    /// parses an entry of `wine:dll_overrides`, which can set several dlls at once like
+    /// `d3d11,dxgi=n`
+    pub fn parse(s: &str, source: &str) -> Option<Vec<Self>> {
+        let (dlls, mode) = s.split_once('=')?;
+        let mode = Mode::from_code(mode)?;
+        let dlls = dlls.split(',').map(|a| a.trim()).collect::<Vec<_>>();
+        if dlls.iter().any(|a| a.is_empty()) {
+            return None;
+        }
+        Some(
+            dlls.into_iter()
+                .map(|dll| Self {
+                    dll: dll.to_owned(),
+                    mode,
+                    source: source.to_owned(),
+                })
+                .collect(),
+        )
+    }
+
+    /// the name wine matches the dll with, `d3d11.dll` being the same as `d3d11`
+    fn key(&self) -> String {
+        let dll = self.dll.to_lowercase();
+        dll.strip_suffix(".dll")
+            .map(|a| a.to_owned())
+            .unwrap_or(dll)
+    }
+}
+
+/// the entry of `wine:dll_overrides` for `dll` and `mode`
+pub fn entry(dll: &str, mode: Mode) -> String {
+    format!("{dll}={}", mode.code())
+}
+
+/// a dll set in two places, only one of which is kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub kept: Override,
+    pub replaced: Override,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is {} as set by {}, instead of {} as set by {}",
+            self.kept.dll,
+            self.kept.mode,
+            self.kept.source,
+            self.replaced.mode,
+            self.replaced.source
+        )
+    }
+}
+
+/// the overrides of the global settings followed by those of the game, the invalid entries being
+/// dropped
+pub fn from_settings(global: &[String], game: &[String]) -> Vec<Override> {
+    let mut out = vec![];
+    for (entries, source) in [(global, "the global settings"), (game, "the game")] {
+        for e in entries.iter().filter(|a| !a.trim().is_empty()) {
+            match Override::parse(e, source) {
+                Some(o) => out.extend(o),
+                None => log::warn!("ignoring invalid dll override \"{e}\" set by {source}"),
+            }
+        }
+    }
+    out
+}
+
+/// the overrides of a `WINEDLLOVERRIDES` value, e.g. `d3d11,dxgi=n;winemenubuilder.exe=d`, the
+/// invalid entries being dropped
+pub fn from_env(value: &str, source: &str) -> Vec<Override> {
+    let mut out = vec![];
+    for e in value.split(';').filter(|a| !a.trim().is_empty()) {
+        match Override::parse(e, source) {
+            Some(o) => out.extend(o),
+            None => log::warn!("ignoring invalid dll override \"{e}\" set by {source}"),
+        }
+    }
+    out
+}
+
+/// keeps the last override of each dll, along with the conflicts between overrides that disagree
+pub fn merge(overrides: Vec<Override>) -> (Vec<Override>, Vec<Conflict>) {
+    let mut out: Vec<Override> = vec![];
+    let mut conflicts = vec![];
+    for o in overrides {
+        match out.iter_mut().find(|a| a.key() == o.key()) {
+            Some(a) => {
+                let replaced = std::mem::replace(a, o.clone());
+                if replaced.mode != o.mode {
+                    conflicts.push(Conflict { kept: o, replaced });
+                }
+            }
+            None => out.push(o),
+        }
+    }
+    (out, conflicts)
+}
+
+/// the value of `WINEDLLOVERRIDES`, e.g. `d3d11,dxgi=n;winemenubuilder.exe=d`
+pub fn to_env(overrides: &[Override]) -> String {
+    Mode::ALL
+        .iter()
+        .filter_map(|m| {
+            let dlls = overrides
+                .iter()
+                .filter(|a| a.mode == *m)
+                .map(|a| &a.dll[..])
+                .collect::<Vec<_>>();
+            (!dlls.is_empty()).then(|| format!("{}={}", dlls.join(","), m.code()))
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
@@ -0,0 +1,250 @@
+//! Installation of dxvk, vkd3d-proton and dxvk_nvapi into wineprefixes.
+//!
+//! The dlls of a component are symlinked into `system32` and `syswow64`, wine's own dlls being
+//! moved to `game_handler/backup/<component>` in the prefix. What is installed is recorded in
+//! `game_handler/components.toml`, so that launching the game again does nothing unless the folder
+//! of the component changed, and so that disabling the component puts wine's dlls back.
+
+use std::path::{Path, PathBuf};
+
+use crate::games::LaunchError;
+
+/// a file of a component : where it comes from, and where it goes relative to `drive_c/windows`
+struct Dll {
+    from: PathBuf,
+    to: String,
+}
+
+/// a component installed in a prefix
+#[derive(Debug, Clone, PartialEq)]
+pub struct Installed {
+    /// the folder the dlls were linked from
+    pub source: String,
+    /// the dlls, relative to `drive_c/windows`
+    pub files: Vec<String>,
+}
+
+impl Installed {
+    /// the version of the component, which is the name of its folder for the official releases
+    pub fn version(&self) -> String {
+        Path::new(&self.source)
+            .file_name()
+            .map(|a| a.to_string_lossy().into_owned())
+            .unwrap_or(self.source.clone())
+    }
+}
+
+fn state_dir(prefix: &Path) -> PathBuf {
+    prefix.join("game_handler")
+}
+
+fn manifest_path(prefix: &Path) -> PathBuf {
+    state_dir(prefix).join("components.toml")
+}
+
+/// the components installed in `prefix`, by name
+pub fn installed(prefix: &Path) -> toml::Table {
+    std::fs::read_to_string(manifest_path(prefix))
+        .ok()
+        .and_then(|s| s.parse::<toml::Table>().ok())
+        .unwrap_or_default()
+}
+
+/// the component `component` installed in `prefix`, if any
+pub fn installed_component(prefix: &Path, component: &str) -> Option<Installed> {
+    let table = installed(prefix);
+    let entry = table.get(component)?.as_table()?;
+    Some(Installed {
+        source: entry.get("source")?.as_str()?.to_owned(),
+        files: entry
+            .get("files")?
+            .as_array()?
+            .iter()
+            .filter_map(|a| a.as_str().map(|a| a.to_owned()))
+            .collect(),
+    })
+}
+
+fn save_component(
+    prefix: &Path,
+    component: &str,
+    installed: Option<&Installed>,
+) -> std::io::Result<()> {
+    let mut table = self::installed(prefix);
+    match installed {
+        Some(i) => {
+            let mut entry = toml::Table::new();
+            entry.insert("source".to_owned(), toml::Value::String(i.source.clone()));
+            entry.insert(
+                "files".to_owned(),
+                toml::Value::Array(
+                    i.files
+                        .iter()
+                        .map(|a| toml::Value::String(a.clone()))
+                        .collect(),
+                ),
+            );
+            table.insert(component.to_owned(), toml::Value::Table(entry));
+        }
+        None => {
+            table.remove(component);
+        }
+    }
+    std::fs::create_dir_all(state_dir(prefix))?;
+    std::fs::write(manifest_path(prefix), table.to_string())
+}
+
+/// the dlls found in `folder/sub`, to be installed in `dest`
+fn list_dlls(
+    component: &str,
+    folder: &str,
+    sub: &str,
+    dest: &str,
+    out: &mut Vec<Dll>,
+) -> Result<(), LaunchError> {
+    let bad_folder =
+        |e: String| LaunchError::BadDllFolder(component.to_owned(), format!("{folder}/{sub}"), e);
+    // the links have to work from inside the prefix
+    let dir = Path::new(folder)
+        .join(sub)
+        .canonicalize()
+        .map_err(|e| bad_folder(e.to_string()))?;
+    for p in std::fs::read_dir(dir).map_err(|e| bad_folder(e.to_string()))? {
+        let from = p.map_err(|e| bad_folder(e.to_string()))?.path();
+        let name = from
+            .file_name()
+            .and_then(|a| a.to_str())
+            .ok_or_else(|| bad_folder(format!("invalid file name {from:?}")))?;
+        out.push(Dll {
+            to: format!("{dest}/{name}"),
+            from,
+        });
+    }
+    Ok(())
+}
+
+/// removes the links to the dlls of a component, and puts back the ones of wine saved in the backup
+fn restore(prefix: &Path, component: &str, files: &[String]) -> std::io::Result<()> {
+    let windows = prefix.join("drive_c/windows");
+    let backup = state_dir(prefix).join("backup").join(component);
+    for f in files {
+        let path = windows.join(f);
+        if path.is_symlink() {
+            std::fs::remove_file(&path)?;
+        }
+        if backup.join(f).exists() {
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            std::fs::rename(backup.join(f), &path)?;
+        }
+    }
+    Ok(())
+}
+
+/// removes the dlls of an installed component, and puts back the ones of wine
+fn uninstall(prefix: &Path, component: &str, installed: &Installed) -> std::io::Result<()> {
+    restore(prefix, component, &installed.files)?;
+    log::info!("uninstalled {component} from {prefix:?}");
+    save_component(prefix, component, None)
+}
+
+fn link(windows: &Path, backup: &Path, d: &Dll) -> std::io::Result<()> {
+    let path = windows.join(&d.to);
+    if path.is_symlink() {
+        std::fs::remove_file(&path)?;
+    } else if path.exists() {
+        // wine's own dll
+        let b = backup.join(&d.to);
+        if let Some(p) = b.parent() {
+            std::fs::create_dir_all(p)?;
+        }
+        std::fs::rename(&path, b)?;
+    }
+    std::os::unix::fs::symlink(&d.from, &path)
+}
+
+/// links the dlls of the component, putting everything back if one of them can't be
+fn install(prefix: &Path, component: &str, source: &str, dlls: &[Dll]) -> std::io::Result<()> {
+    let windows = prefix.join("drive_c/windows");
+    let backup = state_dir(prefix).join("backup").join(component);
+    for (i, d) in dlls.iter().enumerate() {
+        if let Err(e) = link(&windows, &backup, d) {
+            let done = dlls[..=i].iter().map(|d| d.to.clone()).collect::<Vec<_>>();
+            if let Err(e) = restore(prefix, component, &done) {
+                log::error!("couldn't restore the dlls of wine in {prefix:?} : {e}");
+            }
+            return Err(e);
+        }
+    }
+    log::info!("installed {component} from {source} in {prefix:?}");
+    save_component(
+        prefix,
+        component,
+        Some(&Installed {
+            source: source.to_owned(),
+            files: dlls.iter().map(|d| d.to.clone()).collect(),
+        }),
+    )
+}
+
+/// makes sure `component` is installed in `prefix` from `folder` if `enabled`, and that wine's
+/// dlls are back otherwise. Nothing is done if it is already the case.
+pub fn sync(
+    prefix: &Path,
+    is_32bit: bool,
+    component: &str,
+    enabled: bool,
+    folder: &Option<String>,
+    folder_64: &str,
+    folder_32: &str,
+) -> Result<(), LaunchError> {
+    let installed = installed_component(prefix, component);
+    let io_error = |e: std::io::Error| {
+        LaunchError::BadDllFolder(
+            component.to_owned(),
+            folder.clone().unwrap_or_default(),
+            format!("couldn't update {prefix:?} : {e}"),
+        )
+    };
+
+    if !enabled {
+        if let Some(i) = installed {
+            uninstall(prefix, component, &i).map_err(io_error)?;
+        }
+        return Ok(());
+    }
+
+    let folder = folder.clone().unwrap_or_default();
+    if folder.is_empty() {
+        return Err(LaunchError::BadDllFolder(
+            component.to_owned(),
+            folder,
+            "no folder set".to_owned(),
+        ));
+    }
+    let mut dlls = vec![];
+    if is_32bit {
+        list_dlls(component, &folder, folder_32, "system32", &mut dlls)?;
+    } else {
+        list_dlls(component, &folder, folder_64, "system32", &mut dlls)?;
+        list_dlls(component, &folder, folder_32, "syswow64", &mut dlls)?;
+    }
+
+    let windows = prefix.join("drive_c/windows");
+    let up_to_date = installed.as_ref().is_some_and(|i| {
+        i.source == folder
+            && i.files.len() == dlls.len()
+            && dlls.iter().all(|d| {
+                i.files.contains(&d.to)
+                    && std::fs::read_link(windows.join(&d.to)).is_ok_and(|l| l == d.from)
+            })
+    });
+    if !up_to_date {
+        if let Some(i) = &installed {
+            uninstall(prefix, component, i).map_err(io_error)?;
+        }
+        install(prefix, component, &folder, &dlls).map_err(io_error)?;
+    }
+    Ok(())
+}
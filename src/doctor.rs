@@ -0,0 +1,147 @@
+//! Pre-launch validation of games and runners, to find broken entries without having to run them.
+
+use crate::games::{find_executable, Config, Game, LaunchError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// the game may run, but probably not as expected
+    Warning,
+    /// the game won't run
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.message),
+            Severity::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// whether `game` has a path to game that should exist but doesn't
+pub fn path_missing(game: &Game) -> bool {
+    game.runner.uses_path() && !game.path_to_game.exists()
+}
+
+/// lists everything that looks wrong with `game`, from its metadata to its runner and config
+pub fn check_game(game: &Game) -> Vec<Problem> {
+    let mut out = vec![];
+
+    if game.runner.uses_path() {
+        if game.path_to_game.as_os_str().is_empty() {
+            out.push(Problem::error("no path to game set"));
+        } else if !game.path_to_game.exists() {
+            out.push(Problem::error(format!(
+                "path to game {:?} doesn't exist",
+                game.path_to_game
+            )));
+        }
+    }
+    if let Some(b) = &game.box_art {
+        if !std::path::Path::new(b).is_file() {
+            out.push(Problem::warning(format!("box art \"{b}\" doesn't exist")));
+        }
+    }
+
+    // building the command doesn't change anything, see `Runner::prepare`
+    let diagnosed = game.runner.diagnose();
+    if let Err(e) = game
+        .runner
+        .get_command()
+        .and_then(|cmd| cmd.check_program())
+    {
+        // the runner may have found it already
+        let e = e.to_string();
+        if !diagnosed.iter().any(|p| p.message == e) {
+            out.push(Problem::error(e));
+        }
+    }
+    out.extend(diagnosed);
+    out.extend(check_config(&game.config));
+
+    out
+}
+
+#[cfg_attr(windows, allow(unused_mut, unused_variables))]
+fn check_config(cfg: &Config) -> Vec<Problem> {
+    let mut out = vec![];
+
+    #[cfg(unix)]
+    {
+        for (enabled, wrapper) in [(cfg.mangohud, "mangohud"), (cfg.gamescope, "gamescope")] {
+            if enabled && find_executable(wrapper, None).is_none() {
+                out.push(Problem::error(
+                    LaunchError::MissingWrapper(wrapper.to_owned()).to_string(),
+                ));
+            }
+        }
+        if let Some(p) = &cfg.vk_icd_loader {
+            if !std::path::Path::new(p).is_file() {
+                out.push(Problem::error(format!(
+                    "vulkan icd loader \"{p}\" doesn't exist"
+                )));
+            }
+        }
+        if cfg.gamemode && !find_library("libgamemodeauto.so.0") {
+            out.push(Problem::warning(
+                "gamemode is enabled but libgamemodeauto.so.0 couldn't be found",
+            ));
+        }
+    }
+
+    out
+}
+
+/// whether the dynamic linker should be able to find the library `name`
+#[cfg(unix)]
+fn find_library(name: &str) -> bool {
+    // the cache of the dynamic linker, read once for the whole library
+    static LDCONFIG: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    let cache = LDCONFIG.get_or_init(|| {
+        ["ldconfig", "/sbin/ldconfig"]
+            .iter()
+            .find_map(|a| std::process::Command::new(a).arg("-p").output().ok())
+            .map(|a| String::from_utf8_lossy(&a.stdout).into_owned())
+            .unwrap_or_default()
+    });
+    if cache.contains(name) {
+        return true;
+    }
+    std::env::var("LD_LIBRARY_PATH")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|a| !a.is_empty())
+        .chain([
+            "/usr/lib",
+            "/usr/lib64",
+            "/usr/lib32",
+            "/usr/lib/x86_64-linux-gnu",
+            "/usr/local/lib",
+            "/lib",
+            "/lib64",
+        ])
+        .any(|a| std::path::Path::new(a).join(name).exists())
+}
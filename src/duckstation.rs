@@ -9,30 +9,30 @@ pub struct DuckStationRunner {
 }
 
 impl Runner for DuckStationRunner {
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
         let mut args = vec!["--".to_owned(), self.path.clone()];
         if self.fullscreen {
             args.insert(0, "-fullscreen".to_owned())
         }
-        Command {
+        Ok(Command {
             program: self.path_to_duckstation.clone(),
             args,
             envs: std::collections::HashMap::new(),
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["duckstation".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "duckstation" => Some(Command {
+            "duckstation" => Ok(Command {
                 program: self.path_to_duckstation.clone(),
                 args: vec![],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
@@ -0,0 +1,69 @@
+//! Search and quick filters of the games grid.
+
+use crate::collections::Group;
+use crate::games::Game;
+
+/// quick filters shown next to the search box
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// only shows games using this runner
+    pub runner: Option<String>,
+    pub running: bool,
+    pub no_box_art: bool,
+    pub path_missing: bool,
+    /// the sidebar entry selected, if any
+    pub group: Option<crate::collections::Group>,
+}
+
+impl Filter {
+    fn matches(&self, game: &Game) -> bool {
+        self.runner
+            .as_ref()
+            .map(|a| *a == game.runner_id)
+            .unwrap_or(true)
+            && (!self.running || game.is_running)
+            && (!self.no_box_art || game.box_art.is_none())
+            && (!self.path_missing || crate::doctor::path_missing(game))
+            && match &self.group {
+                Some(g) => g.matches(game) && (!game.hidden || *g == Group::Hidden),
+                None => !game.hidden,
+            }
+    }
+}
+
+/// the indices of the games matching both `query` and `filter`. Without a query they are in the
+/// current sort order, otherwise the best matches come first.
+pub fn visible(games: &[Game], query: &str, filter: &Filter) -> Vec<usize> {
+    let mut out = games
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| filter.matches(g))
+        .filter_map(|(i, g)| fuzzy_score(query, &g.name).map(|s| (i, s)))
+        .collect::<Vec<_>>();
+    // the sort is stable, so equal matches stay in the current sort order
+    out.sort_by_key(|(_, s)| std::cmp::Reverse(*s));
+    out.into_iter().map(|(i, _)| i).collect()
+}
+
+/// scores how well `query` matches `name`, if its characters all appear in order (ignoring case
+/// and spaces). Consecutive characters and characters at the start of words score higher.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let name = name.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|a| !a.is_whitespace()) {
+        let found = pos + name[pos..].iter().position(|c| *c == q)?;
+        score += 1;
+        if last_match.map(|a| a + 1 == found).unwrap_or(found == 0) {
+            score += 5;
+        }
+        if found == 0 || !name[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - pos).min(5) as i32;
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
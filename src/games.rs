@@ -33,6 +33,78 @@ pub const RUNNERS: [&str; 11] = [
     "duckstation",
 ];
 
+/// the ids of both built-in and custom runners
+pub fn runner_ids() -> Vec<String> {
+    RUNNERS
+        .iter()
+        .map(|a| a.to_string())
+        .chain(crate::custom::DEFINITIONS.iter().map(|a| a.id.clone()))
+        .collect()
+}
+
+/// programs that `Command::apply_config` may wrap the actual command with
+pub const WRAPPERS: [&str; 2] = ["mangohud", "gamescope"];
+
+/// the reasons why a game may fail to start
+#[derive(Debug, Clone)]
+pub enum LaunchError {
+    /// the program to run (game or emulator) doesn't exist
+    MissingExecutable(String),
+    /// a wrapper such as mangohud or gamescope is enabled but not installed
+    MissingWrapper(String),
+    /// a dxvk, vkd3d or dxvk_nvapi folder is unset or can't be read : (component, path, reason)
+    BadDllFolder(String, String, String),
+    /// the program exists but isn't executable
+    NotExecutable(String),
+    /// the runner doesn't know this subcommand
+    UnknownSubcommand(String),
+    /// the process couldn't be spawned for another reason : (program, reason)
+    Spawn(String, String),
+}
+
+impl std::fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingExecutable(p) => write!(f, "couldn't find executable \"{p}\""),
+            Self::MissingWrapper(p) => write!(f, "{p} is enabled but couldn't be found"),
+            Self::BadDllFolder(c, p, e) => write!(f, "invalid {c} folder \"{p}\" : {e}"),
+            Self::NotExecutable(p) => write!(f, "\"{p}\" is not executable"),
+            Self::UnknownSubcommand(s) => write!(f, "unknown subcommand \"{s}\""),
+            Self::Spawn(p, e) => write!(f, "couldn't start \"{p}\" : {e}"),
+        }
+    }
+}
+
+/// looks for `program` the way a shell would : as a path if it contains a `/`, in `path` (or
+/// `$PATH` if `None`) otherwise.
+pub fn find_executable(program: &str, path: Option<&String>) -> Option<std::path::PathBuf> {
+    if program.is_empty() {
+        None
+    } else if program.contains(std::path::MAIN_SEPARATOR) {
+        let p = std::path::PathBuf::from(program);
+        p.exists().then_some(p)
+    } else {
+        let path = path
+            .map(std::ffi::OsString::from)
+            .or_else(|| std::env::var_os("PATH"))?;
+        std::env::split_paths(&path)
+            .map(|a| a.join(program))
+            .find(|a| a.is_file() && is_executable(a))
+    }
+}
+
+pub fn is_executable(path: &std::path::Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|a| a.is_file() && a.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(windows)]
+    path.is_file()
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Command {
     pub program: String,
@@ -42,7 +114,8 @@ pub struct Command {
 }
 
 impl Command {
-    pub fn run(&self) -> Option<tokio::process::Child> {
+    pub fn run(&self) -> Result<tokio::process::Child, LaunchError> {
+        self.check_program()?;
         let mut cmd = tokio::process::Command::new(self.program.clone());
         cmd.args(self.args.as_slice())
             .envs(self.envs.clone())
@@ -68,19 +141,53 @@ impl Command {
         //         std::env::current_dir().unwrap()
         //     })
         //     .detached();
+        // such that the whole process tree can be found and killed later on
+        #[cfg(unix)]
+        cmd.process_group(0);
         log::info!("running command : {:?}", cmd);
         let out = cmd.spawn();
 
-        if let Err(e) = out {
+        out.map_err(|e| {
             log::error!("error {e} while running command {cmd:?}");
-            None
+            LaunchError::Spawn(self.program.clone(), e.to_string())
+        })
+    }
+
+    /// checks that the program to run exists and can be executed
+    pub fn check_program(&self) -> Result<(), LaunchError> {
+        let path = find_executable(&self.program, self.envs.get("PATH")).ok_or_else(|| {
+            if WRAPPERS.contains(&&self.program[..]) {
+                LaunchError::MissingWrapper(self.program.clone())
+            } else {
+                LaunchError::MissingExecutable(self.program.clone())
+            }
+        })?;
+        if is_executable(&path) {
+            Ok(())
         } else {
-            let out = out.unwrap();
-            Some(out)
+            Err(LaunchError::NotExecutable(
+                path.to_string_lossy().to_string(),
+            ))
         }
     }
 
-    fn apply_config(&mut self, cfg: &Config) {
+    /// starts the command in the foreground, its output going straight to the terminal. Used by
+    /// the command line interface.
+    pub fn spawn_attached(&self) -> std::io::Result<std::process::Child> {
+        let mut cmd = std::process::Command::new(self.program.clone());
+        cmd.args(self.args.as_slice())
+            .envs(self.envs.clone())
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+        if let Some(cwd) = self.cwd.clone() {
+            cmd.current_dir(cwd);
+        }
+        log::info!("running command : {:?}", cmd);
+        cmd.spawn()
+    }
+
+    pub fn apply_config(&mut self, cfg: &Config) {
         #[cfg(unix)]
         if cfg.mangohud {
             self.args.insert(0, self.program.clone());
@@ -135,6 +242,10 @@ impl Command {
         }
 
         for (k, v) in cfg.envs.iter() {
+            // the wine runners already merged it with their own overrides
+            if k == "WINEDLLOVERRIDES" && self.envs.contains_key(k) {
+                continue;
+            }
             #[cfg(unix)]
             if cfg.gamemode {
                 if k == "LD_PRELOAD" {
@@ -153,77 +264,188 @@ impl Command {
     }
 }
 
+/// quotes `s` such that it can be pasted in a POSIX shell
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c))
+    {
+        s.to_owned()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(cwd) = &self.cwd {
+            write!(f, "cd {} && ", shell_quote(&cwd.to_string_lossy()))?;
+        }
+        let mut envs = self.envs.iter().collect::<Vec<_>>();
+        envs.sort();
+        for (k, v) in envs {
+            write!(f, "{k}={} ", shell_quote(v))?;
+        }
+        write!(f, "{}", shell_quote(&self.program))?;
+        for a in &self.args {
+            write!(f, " {}", shell_quote(a))?;
+        }
+        Ok(())
+    }
+}
+
 pub trait Runner {
-    fn get_command(&self) -> Command {
-        Command::default()
+    fn get_command(&self) -> Result<Command, LaunchError> {
+        Ok(Command::default())
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec![]
     }
-    fn get_subcommand_command(&self, _command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
+        Err(LaunchError::UnknownSubcommand(command))
+    }
+    /// runner specific problems that would prevent the game from running properly
+    fn diagnose(&self) -> Vec<crate::doctor::Problem> {
+        vec![]
+    }
+    /// a subcommand to run once the game is added, to set up what it needs to run
+    fn setup_subcommand(&self) -> Option<String> {
+        None
+    }
+    /// the state of what the runner set up, shown in the game settings as (label, value)
+    fn info(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+    /// the wineprefix the game runs in, for the runners using wine
+    fn wineprefix(&self) -> Option<String> {
         None
     }
+    /// what to ask before running `subcommand`, for the ones that can't be undone
+    fn confirm_subcommand(&self, _subcommand: &str) -> Option<String> {
+        None
+    }
+    /// whether the path to the game is a file or folder that should exist, unlike e.g. a steam
+    /// AppID
+    fn uses_path(&self) -> bool {
+        true
+    }
+    /// sets up what the game needs right before it is launched, e.g. the dlls of a wineprefix.
+    /// `get_command` doesn't change anything, since it is also used to check games.
+    fn prepare(&self) -> Result<(), LaunchError> {
+        Ok(())
+    }
 }
 
 pub struct DummyRunner();
-impl Runner for DummyRunner {}
+impl Runner for DummyRunner {
+    fn uses_path(&self) -> bool {
+        false
+    }
+}
 
 pub struct Game {
+    /// stored in the toml, see `id`
+    pub uuid: String,
     pub name: String,
     pub box_art: Option<String>,
     pub release_year: Option<isize>,
-    pub image: image::RgbaImage,
+    /// the thumbnail of the box art. The handle is kept so that the renderer uploads it once.
+    pub image: iced::widget::image::Handle,
+    pub favorite: bool,
+    /// only shown in the "hidden" entry of the sidebar
+    pub hidden: bool,
+    pub tags: Vec<String>,
+    /// the user-defined collections the game belongs to
+    pub collections: Vec<String>,
     pub path_to_game: std::path::PathBuf,
     pub runner_id: String,
     pub runner: Box<dyn Runner>,
     pub config: Config,
 
     pub path_to_toml: std::path::PathBuf,
+    /// when the game's toml was created
+    pub date_added: Option<std::time::SystemTime>,
 
     pub bare_config: crate::config::Cfg,
 
     pub current_log: String,
     pub no_sleep: Option<nosleep::NoSleep>,
 
-    pub time_played: std::time::Duration,
+    pub playtime: crate::playtime::GameStats,
     pub time_started: Option<std::time::SystemTime>,
-    pub time_played_this_year: std::time::Duration,
 
     pub is_running: bool,
     pub cmd_to_run: Option<Command>,
+    /// why the last launch failed, if it did
+    pub launch_error: Option<LaunchError>,
     pub psub_sender: Option<iced::futures::channel::mpsc::Sender<PSubInput>>,
 
-    pub managed_processes: Vec<sysinfo::Pid>,
+    /// the processes spawned by the game that are still alive
+    pub managed_processes: Vec<(sysinfo::Pid, String)>,
 }
 
 impl Game {
-    pub fn from_toml(
-        path: &std::path::PathBuf,
-        default: &std::path::Path,
-        play_time_db: &HashMap<String, std::time::Duration>,
-        play_time_ty_db: &HashMap<String, std::time::Duration>,
-    ) -> Self {
-        crate::config::Cfg::from_toml(path).into_game(
-            default,
-            path.to_owned(),
-            play_time_db,
-            play_time_ty_db,
-        )
-    }
-
-    pub fn run(&mut self) {
-        let mut cmd = self.runner.get_command();
+    /// the key under which per-game data (such as playtime) is stored
+    pub fn id(&self) -> String {
+        self.uuid.clone()
+    }
+
+    /// where the logs of the last run of the game are saved
+    pub fn log_path(&self) -> std::path::PathBuf {
+        crate::DIRS
+            .data_dir()
+            .join("logs")
+            .join(self.uuid.clone() + ".log")
+    }
+
+    pub fn run(&mut self) -> Result<(), LaunchError> {
+        self.start(None)
+    }
+
+    pub fn run_subcommand(&mut self, a: String) -> Result<(), LaunchError> {
+        self.start(Some(a))
+    }
+
+    /// the fully configured command to launch the game (or one of its runner's subcommands),
+    /// after checking that it can actually be run. The runner is prepared for the game once its
+    /// command is checked, so this is only called right before running the command.
+    pub fn launch_command(&self, subcommand: Option<String>) -> Result<Command, LaunchError> {
+        if subcommand.is_some() {
+            return self.command(subcommand);
+        }
+        self.command(None)?;
+        self.runner.prepare()?;
+        // preparing the runner can change the command, e.g. the dll overrides of a wineprefix
+        self.command(None)
+    }
+
+    fn command(&self, subcommand: Option<String>) -> Result<Command, LaunchError> {
+        let mut cmd = if let Some(s) = subcommand {
+            self.runner.get_subcommand_command(s)?
+        } else {
+            self.runner.get_command()?
+        };
+        // the wrapped program is checked here, wrappers once they are applied
+        cmd.check_program()?;
         cmd.apply_config(&self.config);
-        self.cmd_to_run = Some(cmd);
-        self.is_running = true;
+        cmd.check_program()?;
+        Ok(cmd)
     }
 
-    pub fn run_subcommand(&mut self, a: String) {
-        let cmd = self.runner.get_subcommand_command(a);
-        if let Some(mut cmd) = cmd {
-            cmd.apply_config(&self.config);
-            self.cmd_to_run = Some(cmd);
-            self.is_running = true;
+    /// gets the command ready to be run by the process subscription, or records why it can't be
+    fn start(&mut self, subcommand: Option<String>) -> Result<(), LaunchError> {
+        match self.launch_command(subcommand) {
+            Ok(cmd) => {
+                self.launch_error = None;
+                self.cmd_to_run = Some(cmd);
+                self.is_running = true;
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("couldn't launch {} : {e}", self.name);
+                self.launch_error = Some(e.clone());
+                Err(e)
+            }
         }
     }
 
@@ -232,6 +454,162 @@ impl Game {
     }
 }
 
+/// a random (version 4) UUID
+pub fn new_id() -> String {
+    let mut b: [u8; 16] = rand::random();
+    b[6] = (b[6] & 0x0f) | 0x40;
+    b[8] = (b[8] & 0x3f) | 0x80;
+    let h = b.iter().map(|a| format!("{a:02x}")).collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &h[..8],
+        &h[8..12],
+        &h[12..16],
+        &h[16..20],
+        &h[20..]
+    )
+}
+
+/// a game toml that couldn't be loaded
+#[derive(Debug, Clone)]
+pub struct BrokenEntry {
+    pub path: std::path::PathBuf,
+    pub error: String,
+}
+
+/// loads every game found in the config directory, along with the files that couldn't be loaded.
+/// Games without an id, which used to be keyed by the name of their toml, get one and their
+/// playtime history and banner follow.
+pub fn load_games(playtime_db: &mut crate::playtime::PlaytimeDb) -> (Vec<Game>, Vec<BrokenEntry>) {
+    let dir = match std::fs::read_dir(crate::DIRS.config_dir().join("games")) {
+        Ok(a) => a,
+        Err(e) => {
+            log::error!("couldn't read the games directory : {e}");
+            return (vec![], vec![]);
+        }
+    };
+
+    let mut broken = vec![];
+    let mut cfgs = vec![];
+    for entry in dir {
+        let path = match entry {
+            Ok(a) => a.path(),
+            Err(e) => {
+                log::error!("couldn't read the games directory : {e}");
+                continue;
+            }
+        };
+        // leaves aside backups and other files left by editors
+        if path.extension().and_then(|a| a.to_str()) != Some("toml") {
+            continue;
+        }
+        match crate::config::Cfg::try_from_toml(&path) {
+            Ok(cfg) => cfgs.push((path, cfg)),
+            Err(error) => broken.push(BrokenEntry { path, error }),
+        }
+    }
+
+    assign_ids(&mut cfgs, &[], playtime_db);
+
+    let settings = crate::DIRS.config_dir().join("settings.toml");
+    let mut games = vec![];
+    for (path, cfg) in cfgs {
+        match cfg.into_game(&settings, path.clone(), playtime_db) {
+            Ok(g) => games.push(g),
+            Err(error) => broken.push(BrokenEntry { path, error }),
+        }
+    }
+    for b in broken.iter() {
+        log::error!("couldn't load {:?} : {}", b.path, b.error);
+    }
+    (games, broken)
+}
+
+/// loads a single game toml, e.g. after fixing a broken entry, next to the `games` already loaded
+pub fn load_game(
+    path: &std::path::Path,
+    games: &[Game],
+    playtime_db: &mut crate::playtime::PlaytimeDb,
+) -> Result<Game, String> {
+    let mut cfgs = vec![(path.to_owned(), crate::config::Cfg::try_from_toml(path)?)];
+    assign_ids(&mut cfgs, games, playtime_db);
+    let (path, cfg) = cfgs.remove(0);
+    cfg.into_game(
+        &crate::DIRS.config_dir().join("settings.toml"),
+        path,
+        playtime_db,
+    )
+}
+
+/// gives an id to the games of `cfgs` which lack one, and moves their playtime history and
+/// downloaded banner (if no other game, in `cfgs` or in the already loaded `games`, uses it) to it
+fn assign_ids(
+    cfgs: &mut [(std::path::PathBuf, crate::config::Cfg)],
+    games: &[Game],
+    playtime_db: &mut crate::playtime::PlaytimeDb,
+) {
+    let banner_users = |b: &str, cfgs: &[(std::path::PathBuf, crate::config::Cfg)]| {
+        cfgs.iter()
+            .filter(|(_, c)| c.0.get("box_art").map(|a| a.as_string()).as_deref() == Some(b))
+            .count()
+            + games
+                .iter()
+                .filter(|g| g.box_art.as_deref() == Some(b))
+                .count()
+    };
+    let mut migrated = HashMap::new();
+    for i in 0..cfgs.len() {
+        if cfgs[i].1 .0.contains_key("id") {
+            continue;
+        }
+        let id = new_id();
+        let (path, cfg) = &cfgs[i];
+        let mut cfg = cfg.clone();
+        cfg.0
+            .insert("id".to_owned(), crate::config::CValue::Str(id.clone()));
+        let mut changed = vec![("id", id.clone())];
+
+        if let Some(b) = cfg.0.get("box_art").map(|a| a.as_string()) {
+            let old = std::path::Path::new(&b);
+            if crate::library::is_managed_banner(&b) && banner_users(&b, cfgs) == 1 {
+                let new = old.with_file_name(format!(
+                    "{id}-{}",
+                    old.file_name().unwrap_or_default().to_string_lossy()
+                ));
+                match std::fs::rename(old, &new) {
+                    Ok(()) => {
+                        let new = new.to_string_lossy().into_owned();
+                        cfg.0.insert(
+                            "box_art".to_owned(),
+                            crate::config::CValue::PickFile(new.clone()),
+                        );
+                        changed.push(("box_art", new));
+                    }
+                    Err(e) => log::error!("couldn't rename banner {b} : {e}"),
+                }
+            }
+        }
+
+        // the rest of the toml is left alone, as `cfg` lacks what it couldn't read
+        if let Err(e) = crate::config::set_metadata_keys(path, &changed) {
+            log::error!("couldn't give an id to {path:?} : {e}");
+            continue;
+        }
+        log::info!("gave id {id} to {path:?}");
+        migrated.insert(
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            id,
+        );
+        cfgs[i].1 = cfg;
+    }
+    if !migrated.is_empty() {
+        playtime_db.rename_ids(&migrated);
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Config {
     #[cfg(unix)]
@@ -250,4 +628,5 @@ pub struct Config {
     pub gamescope_params: Vec<String>,
     #[cfg(unix)]
     pub gamemode: bool,
+    pub tracked_process: Option<crate::process_tree::TrackingRule>,
 }
@@ -9,7 +9,9 @@ use iced::{
         widget::{Operation, Tree},
         Clipboard, Layout, Shell, Widget,
     },
-    event, Element, Event, Length, Point, Rectangle, Size,
+    event,
+    widget::scrollable::Viewport,
+    Element, Event, Length, Point, Rectangle, Size,
 };
 
 #[allow(missing_debug_implementations)]
@@ -319,6 +321,7 @@ where
             .iter()
             .zip(&state.children)
             .zip(layout.children())
+            .filter(|(_, layout)| layout.bounds().intersects(viewport))
         {
             element
                 .as_widget()
@@ -364,6 +367,50 @@ fn build_grid(
     Node::with_children(Size::new(grid_width, grid_height), nodes)
 }
 
+/// The cells of a grid using [`Strategy::ColumnWidth`](Strategy) with cells of a fixed size,
+/// which intersect the viewport of the scrollable containing it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    pub first: usize,
+    /// exclusive
+    pub last: usize,
+    /// the height of the rows before the first cell
+    pub above: f32,
+    /// the height of the rows after the last cell
+    pub below: f32,
+}
+
+/// Finds which of `len` cells of `cell_size` are visible in `viewport`, or all of them if the
+/// viewport isn't known.
+pub fn visible_window(len: usize, cell_size: Size, viewport: Option<&Viewport>) -> Window {
+    let Some(viewport) = viewport else {
+        return Window {
+            first: 0,
+            last: len,
+            above: 0.,
+            below: 0.,
+        };
+    };
+    let bounds = viewport.bounds();
+    let columns = ((bounds.width / cell_size.width).floor() as usize).max(1);
+    let rows = len.div_ceil(columns);
+    let total = rows as f32 * cell_size.height;
+    // the viewport can be outdated when the number of cells changes
+    let offset = viewport
+        .absolute_offset()
+        .y
+        .min(total - bounds.height)
+        .max(0.);
+    let first_row = ((offset / cell_size.height).floor() as usize).min(rows);
+    let last_row = (((offset + bounds.height) / cell_size.height).ceil() as usize).min(rows);
+    Window {
+        first: first_row * columns,
+        last: (last_row * columns).min(len),
+        above: first_row as f32 * cell_size.height,
+        below: (rows - last_row) as f32 * cell_size.height,
+    }
+}
+
 impl<'a, Message, Theme, Renderer> From<Grid<'a, Message, Theme, Renderer>>
     for Element<'a, Message, Theme, Renderer>
 where
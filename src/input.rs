@@ -0,0 +1,239 @@
+//! Navigation without a mouse. Keyboard and gamepad events are turned into device independent
+//! `Action`s, which are then applied by `MainGUI::handle_input`, so that the navigation can be
+//! driven by synthetic actions.
+
+use iced::keyboard::{key::Named, Key, Modifiers};
+
+/// how far a stick has to be pushed to move the selection, and released to move it again
+const STICK_PRESSED: f32 = 0.6;
+const STICK_RELEASED: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// runs the selected game
+    Launch,
+    /// closes the current overlay or page, and goes back to the grid
+    Back,
+    /// kills the selected game
+    Kill,
+    ToggleCouchMode,
+}
+
+/// arrows and vim keys to move, Enter, Escape, X to kill and F11 for couch mode
+pub fn from_key(key: &Key, modifiers: Modifiers) -> Option<Action> {
+    if modifiers.command() || modifiers.alt() {
+        return None;
+    }
+    match key.as_ref() {
+        Key::Named(Named::ArrowUp) | Key::Character("k") => Some(Action::Up),
+        Key::Named(Named::ArrowDown) | Key::Character("j") => Some(Action::Down),
+        Key::Named(Named::ArrowLeft) | Key::Character("h") => Some(Action::Left),
+        Key::Named(Named::ArrowRight) | Key::Character("l") => Some(Action::Right),
+        Key::Named(Named::Enter) => Some(Action::Launch),
+        Key::Named(Named::Escape) => Some(Action::Back),
+        Key::Character("x") => Some(Action::Kill),
+        Key::Named(Named::F11) => Some(Action::ToggleCouchMode),
+        _ => None,
+    }
+}
+
+/// the d-pad to move, A to launch, B to go back, X to kill and Start for couch mode
+pub fn from_button(button: gilrs::Button) -> Option<Action> {
+    match button {
+        gilrs::Button::DPadUp => Some(Action::Up),
+        gilrs::Button::DPadDown => Some(Action::Down),
+        gilrs::Button::DPadLeft => Some(Action::Left),
+        gilrs::Button::DPadRight => Some(Action::Right),
+        gilrs::Button::South => Some(Action::Launch),
+        gilrs::Button::East => Some(Action::Back),
+        gilrs::Button::West => Some(Action::Kill),
+        gilrs::Button::Start => Some(Action::ToggleCouchMode),
+        _ => None,
+    }
+}
+
+/// turns the left stick into single steps, like the d-pad
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stick {
+    x_pressed: bool,
+    y_pressed: bool,
+}
+
+impl Stick {
+    pub fn update(&mut self, axis: gilrs::Axis, value: f32) -> Option<Action> {
+        let (pressed, action) = match axis {
+            gilrs::Axis::LeftStickX => (
+                &mut self.x_pressed,
+                if value > 0. {
+                    Action::Right
+                } else {
+                    Action::Left
+                },
+            ),
+            // pushing the stick up gives positive values
+            gilrs::Axis::LeftStickY => (
+                &mut self.y_pressed,
+                if value > 0. { Action::Up } else { Action::Down },
+            ),
+            _ => return None,
+        };
+        if !*pressed && value.abs() > STICK_PRESSED {
+            *pressed = true;
+            Some(action)
+        } else {
+            if value.abs() < STICK_RELEASED {
+                *pressed = false;
+            }
+            None
+        }
+    }
+}
+
+/// the actions of every gamepad, read on a separate thread as gilrs blocks
+pub fn gamepad() -> iced::Subscription<Action> {
+    use iced::futures::StreamExt;
+
+    // the thread only starts when the subscription is, not each time it is declared
+    let stream = iced::futures::stream::once(async {
+        let (sender, receiver) = iced::futures::channel::mpsc::unbounded();
+        std::thread::spawn(move || {
+            let mut gilrs = match gilrs::Gilrs::new() {
+                Ok(g) => g,
+                Err(e) => {
+                    log::error!("couldn't access gamepads : {e}");
+                    return;
+                }
+            };
+            let mut stick = Stick::default();
+            // stops once the subscription is dropped, even when no gamepad sends events
+            while !sender.is_closed() {
+                while let Some(event) = gilrs.next_event() {
+                    let action = match event.event {
+                        gilrs::EventType::ButtonPressed(b, _) => from_button(b),
+                        gilrs::EventType::AxisChanged(a, v, _) => stick.update(a, v),
+                        _ => None,
+                    };
+                    if let Some(a) = action {
+                        if sender.unbounded_send(a).is_err() {
+                            return;
+                        }
+                    }
+                }
+                std::thread::sleep(std::time::Duration::from_millis(16));
+            }
+        });
+        receiver
+    })
+    .flatten();
+    iced::Subscription::run_with_id("gamepad", stream)
+}
+
+/// the game selected after moving in direction `action` in a grid of `columns` showing the
+/// games `visible`, starting from `selected`. Without a selection, the first game is selected.
+pub fn navigate(
+    visible: &[usize],
+    selected: Option<usize>,
+    columns: usize,
+    action: Action,
+) -> Option<usize> {
+    let columns = columns.max(1);
+    let Some(pos) = selected.and_then(|s| visible.iter().position(|a| *a == s)) else {
+        return visible.first().copied();
+    };
+    let pos = match action {
+        Action::Up if pos >= columns => pos - columns,
+        Action::Down if pos + columns < visible.len() => pos + columns,
+        // moves to the last game when the row below isn't complete
+        Action::Down if pos / columns < (visible.len() - 1) / columns => visible.len() - 1,
+        Action::Left => pos.saturating_sub(1),
+        Action::Right => (pos + 1).min(visible.len() - 1),
+        _ => pos,
+    };
+    Some(visible[pos])
+}
+
+/// how many games fit in a row of `width`, the list and detail views having a single column
+/// outside of couch mode
+pub fn columns(
+    couch_mode: bool,
+    view: crate::views::LibraryView,
+    width: Option<f32>,
+    cell_width: f32,
+) -> usize {
+    match (couch_mode, view) {
+        (false, crate::views::LibraryView::List | crate::views::LibraryView::Detail) => 1,
+        _ => width
+            .map(|w| (w / cell_width).floor() as usize)
+            .unwrap_or(1)
+            .max(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::LibraryView;
+
+    #[test]
+    fn navigate_partial_last_row() {
+        // 3 columns, the last row only has the game 6
+        let visible = [0, 1, 2, 3, 4, 5, 6];
+        assert_eq!(navigate(&visible, Some(4), 3, Action::Down), Some(6));
+        assert_eq!(navigate(&visible, Some(3), 3, Action::Down), Some(6));
+        // already on the last row
+        assert_eq!(navigate(&visible, Some(6), 3, Action::Down), Some(6));
+        assert_eq!(navigate(&visible, Some(6), 3, Action::Right), Some(6));
+        assert_eq!(navigate(&visible, Some(6), 3, Action::Up), Some(3));
+        // a complete last row
+        assert_eq!(navigate(&visible[..6], Some(2), 3, Action::Down), Some(5));
+        assert_eq!(navigate(&visible[..6], Some(5), 3, Action::Down), Some(5));
+    }
+
+    #[test]
+    fn navigate_without_selection() {
+        let visible = [4, 2, 7];
+        for action in [Action::Up, Action::Down, Action::Left, Action::Right] {
+            assert_eq!(navigate(&visible, None, 2, action), Some(4));
+        }
+        assert_eq!(navigate(&[], None, 2, Action::Down), None);
+    }
+
+    #[test]
+    fn navigate_from_hidden_selection() {
+        // the selected game was filtered out
+        let visible = [4, 2, 7];
+        assert_eq!(navigate(&visible, Some(3), 2, Action::Right), Some(4));
+        assert_eq!(navigate(&[], Some(3), 2, Action::Right), None);
+    }
+
+    #[test]
+    fn navigate_in_visible_order() {
+        let visible = [4, 2, 7, 0];
+        assert_eq!(navigate(&visible, Some(2), 2, Action::Down), Some(0));
+        assert_eq!(navigate(&visible, Some(7), 2, Action::Up), Some(4));
+        assert_eq!(navigate(&visible, Some(4), 2, Action::Left), Some(4));
+        assert_eq!(navigate(&visible, Some(4), 2, Action::Right), Some(2));
+    }
+
+    #[test]
+    fn navigate_single_column() {
+        let visible = [0, 1, 2];
+        for view in [LibraryView::List, LibraryView::Detail] {
+            let columns = columns(false, view, Some(1000.), 100.);
+            assert_eq!(columns, 1);
+            assert_eq!(navigate(&visible, Some(0), columns, Action::Down), Some(1));
+            assert_eq!(navigate(&visible, Some(1), columns, Action::Up), Some(0));
+            assert_eq!(navigate(&visible, Some(2), columns, Action::Down), Some(2));
+            assert_eq!(navigate(&visible, Some(0), columns, Action::Up), Some(0));
+        }
+        // couch mode always shows a grid
+        assert_eq!(columns(true, LibraryView::List, Some(1000.), 100.), 10);
+        assert_eq!(columns(false, LibraryView::Grid, Some(1000.), 300.), 3);
+        assert_eq!(columns(false, LibraryView::Grid, None, 300.), 1);
+        assert_eq!(columns(false, LibraryView::Grid, Some(100.), 300.), 1);
+    }
+}
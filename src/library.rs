@@ -0,0 +1,148 @@
+//! Removal and duplication of games, along with the files that belong to them.
+
+use std::path::{Path, PathBuf};
+
+/// moves `path` to the freedesktop trash of the user, such that it can be restored from a file
+/// manager
+pub fn move_to_trash(path: &Path) -> std::io::Result<()> {
+    let path = path.canonicalize()?;
+    let trash = directories::BaseDirs::new()
+        .ok_or(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no home directory",
+        ))?
+        .data_dir()
+        .join("Trash");
+    std::fs::create_dir_all(trash.join("files"))?;
+    std::fs::create_dir_all(trash.join("info"))?;
+
+    let file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let mut name = file_name.clone();
+    let mut i = 1;
+    while trash.join("files").join(&name).exists()
+        || trash
+            .join("info")
+            .join(name.clone() + ".trashinfo")
+            .exists()
+    {
+        i += 1;
+        name = format!("{file_name}.{i}");
+    }
+
+    let info = trash.join("info").join(name.clone() + ".trashinfo");
+    std::fs::write(
+        &info,
+        format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            url_encode(&path.to_string_lossy()),
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+        ),
+    )?;
+    let dest = trash.join("files").join(&name);
+    // renaming fails across filesystems
+    if std::fs::rename(&path, &dest).is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = copy_all(&path, &dest) {
+        // a trashinfo without its file would show up as a broken entry, and the original is
+        // still whole
+        let _ = std::fs::remove_file(&info);
+        let _ = remove_all(&dest);
+        return Err(e);
+    }
+    // the copy in the trash is the only complete one once the original is partly removed
+    remove_all(&path).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("moved to the trash, but couldn't remove everything from {path:?} : {e}"),
+        )
+    })
+}
+
+/// copies a file or a folder with everything inside, keeping symlinks as they are (wineprefixes
+/// link to `/` for instance)
+fn copy_all(from: &Path, to: &Path) -> std::io::Result<()> {
+    let meta = std::fs::symlink_metadata(from)?;
+    if meta.file_type().is_symlink() {
+        let target = std::fs::read_link(from)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, to)?;
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_file(target, to)?;
+    } else if meta.is_dir() {
+        std::fs::create_dir(to)?;
+        for e in std::fs::read_dir(from)? {
+            let e = e?;
+            copy_all(&e.path(), &to.join(e.file_name()))?;
+        }
+    } else {
+        std::fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+/// removes a file or a folder with everything inside, without following symlinks
+fn remove_all(path: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// the games running in the wineprefix `prefix`
+pub fn prefix_users<'a>(
+    games: &'a [crate::games::Game],
+    prefix: &str,
+) -> Vec<&'a crate::games::Game> {
+    let canonical = |p: &str| Path::new(p).canonicalize().unwrap_or(PathBuf::from(p));
+    let prefix = canonical(prefix);
+    games
+        .iter()
+        .filter(|g| {
+            g.runner
+                .wineprefix()
+                .is_some_and(|p| canonical(&p) == prefix)
+        })
+        .collect()
+}
+
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+/// whether `box_art` was downloaded by the launcher, and so belongs to it
+pub fn is_managed_banner(box_art: &str) -> bool {
+    Path::new(box_art).starts_with(crate::DIRS.data_dir().join("banners"))
+}
+
+/// copies a downloaded banner so that a duplicated game doesn't share it with the original
+pub fn copy_banner(box_art: &str) -> std::io::Result<PathBuf> {
+    let from = Path::new(box_art);
+    let dir = crate::DIRS.data_dir().join("banners");
+    let stem = from.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = from
+        .extension()
+        .map(|a| format!(".{}", a.to_string_lossy()))
+        .unwrap_or_default();
+    let mut i = 1;
+    let mut to = dir.join(format!("{stem}-{i}{ext}"));
+    while to.exists() {
+        i += 1;
+        to = dir.join(format!("{stem}-{i}{ext}"));
+    }
+    std::fs::copy(from, &to)?;
+    Ok(to)
+}
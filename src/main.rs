@@ -1,33 +1,48 @@
 mod citra;
+mod cli;
+mod collections;
 mod config;
+mod custom;
 mod date;
+mod dll_overrides;
+#[cfg(unix)]
+mod dlls;
+mod doctor;
 mod duckstation;
+mod filter;
 mod games;
 mod grid_widget;
+mod input;
+mod library;
 mod mame;
 mod native;
 mod pcsx2;
+mod playtime;
 mod process_subscription;
+mod process_tree;
 mod rpcs3;
 mod ryujinx;
 mod sort;
+mod stats;
 mod steam;
 mod theme;
+mod thumbnails;
 mod ui;
 #[cfg(unix)]
 mod umu;
+mod views;
 mod vita3k;
 #[cfg(unix)]
 mod wine;
+mod wine_builds;
+mod winetricks;
 mod yuzu;
 
 use std::collections::HashMap;
 
 use config::{get_default_config_with_vals, CValue, Cfg};
 use games::Game;
-use iced::color;
 use iced::futures::channel::mpsc::Sender;
-use iced::theme::Palette;
 use iced::Task as Command;
 
 pub const WIDGET_HEIGHT: u16 = 31;
@@ -62,6 +77,10 @@ fn main() -> iced::Result {
         log::error!("couldn't ensure the config directory existence or integrity : {e}");
         panic!()
     }
+    if let Err(e) = std::fs::create_dir_all(DIRS.config_dir().join("runners")) {
+        log::error!("couldn't ensure the config directory existence or integrity : {e}");
+        panic!()
+    }
 
     log::info!("checking data directory {:?}...", DIRS.data_dir());
     if let Err(e) = std::fs::create_dir_all(DIRS.data_dir().join("banners")) {
@@ -72,26 +91,22 @@ fn main() -> iced::Result {
         log::error!("couldn't ensure the data directory existence or integrity : {e}");
         panic!()
     }
+    if let Err(e) = std::fs::create_dir_all(DIRS.data_dir().join("logs")) {
+        log::error!("couldn't ensure the data directory existence or integrity : {e}");
+        panic!()
+    }
+    if let Err(e) = std::fs::create_dir_all(DIRS.data_dir().join("prefixes")) {
+        log::error!("couldn't ensure the data directory existence or integrity : {e}");
+        panic!()
+    }
+    if let Err(e) = std::fs::create_dir_all(DIRS.cache_dir().join("thumbnails")) {
+        log::error!("couldn't ensure the cache directory existence : {e}");
+    }
 
-    // log::info!("Loading theme");
-    // let file = if let Ok(ct) = std::fs::read_to_string(DIRS.data_dir().join("theme.toml")) {
-    //     ct
-    // } else {
-    //     String::new()
-    // };
-
-    // if std::env::args().nth(1).unwrap_or(String::new()) == "--dump-default-theme".to_string() {
-    //     println!(
-    //         "{}",
-    //         toml::to_string_pretty(&crate::theme::CurrentTheme::default()).unwrap()
-    //     );
-    //     panic!("Error: Success");
-    // }
-
-    // *crate::theme::CURRENT_THEME.lock().unwrap() = toml::from_str(&file[..])
-    //     .unwrap_or_else(|_| {
-    //         log::error!("Theme file \"%data_dir%/theme.toml\" not found or wrongly formatted : defaulting to embedded theme.");
-    //         toml::from_str(theme::EMBEDDED_THEME).unwrap()});
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code)
+    }
 
     log::info!("UI starting");
     // MainGUI::run(Settings {
@@ -111,6 +126,8 @@ pub enum GridStatus {
     AddGame,
     GamesSettings,
     Logs,
+    Stats,
+    BrokenEntries,
 }
 
 impl std::convert::TryFrom<usize> for GridStatus {
@@ -123,6 +140,8 @@ impl std::convert::TryFrom<usize> for GridStatus {
             2 => Ok(Self::AddGame),
             3 => Ok(Self::GamesSettings),
             4 => Ok(Self::Logs),
+            5 => Ok(Self::Stats),
+            6 => Ok(Self::BrokenEntries),
             _ => Err(()),
         }
     }
@@ -143,36 +162,61 @@ pub struct MainGUI {
     sgdb_query: String,
     sgdb_other_possibilities: Vec<steamgriddb_api::search::SearchResult>,
     sgdb_async_status: SGDBAsyncStatus,
-    time_played_db: HashMap<String, std::time::Duration>,
-    time_played_ty_db: HashMap<String, std::time::Duration>,
+    playtime_db: playtime::PlaytimeDb,
     sort_alg: sort::Sorts,
+    sort_descending: bool,
     log: iced::widget::text_editor::Content,
+    /// the result of the last check of the selected game, shown in its settings
+    doctor_report: Option<Vec<doctor::Problem>>,
+    /// what the runner of the selected game set up, read when its settings are shown as it reads
+    /// files
+    runner_info: Vec<(String, String)>,
+    stats_period: stats::Period,
+    /// computed when the stats are shown, and again when the sessions or the period change
+    stats: stats::Stats,
+    search: String,
+    filter: filter::Filter,
+    sidebar_open: bool,
+    /// whether the deletion of the selected game is waiting for confirmation
+    confirm_delete: bool,
+    /// the subcommand of the selected game waiting for confirmation, see
+    /// `Runner::confirm_subcommand`
+    confirm_subcommand: Option<String>,
+    /// why `confirm_subcommand` can't be run, shown in its dialog
+    subcommand_refused: Option<String>,
+    smart_collections: Vec<collections::SmartCollection>,
+    /// the game tomls that couldn't be loaded
+    broken: Vec<games::BrokenEntry>,
+    /// only the rows of the games grid in this viewport are built
+    grid_viewport: Option<iced::widget::scrollable::Viewport>,
+    library_view: views::LibraryView,
+    /// fullscreen with large box arts, to be used with a gamepad
+    couch_mode: bool,
+    theme: theme::CustomTheme,
+    theme_type: theme::ThemeType,
+    /// the width of the box arts of the grid, in logical pixels
+    cover_width: f32,
+    /// counts the scrolls changing `cover_width`, to only save it once the last one is settled
+    cover_width_scrolls: u64,
+    /// the scale factor of the window, `cover_width` being multiplied by it to get physical pixels
+    scale_factor: f32,
+    /// the width thumbnails are decoded at, 0 until the scale factor is known
+    thumbnail_width: u32,
+    modifiers: iced::keyboard::Modifiers,
+    /// the verbs listed by winetricks, once loaded
+    winetricks_verbs: Option<Result<Vec<winetricks::Verb>, String>>,
+    winetricks_picker: bool,
+    winetricks_search: String,
+    /// the dll typed in the settings, added to `wine:dll_overrides` once submitted
+    dll_override_draft: String,
+    /// the wine builds found on the system, with their version
+    wine_builds: Vec<wine_builds::WineBuild>,
 }
 
 impl MainGUI {
-    fn update_db(&self) {
-        if let Ok(mut file) = std::fs::File::create(DIRS.data_dir().join("times.toml")) {
-            let mut db = HashMap::new();
-            self.time_played_db
-                .iter()
-                .map(|(k, v)| db.insert(k, v.as_secs() as i64))
-                .last();
-            let _ = std::io::Write::write_fmt(
-                &mut file,
-                format_args!("{}", toml::to_string(&db).unwrap_or("42".to_owned())),
-            );
-        }
-        if let Ok(mut file) = std::fs::File::create(DIRS.data_dir().join("times_ty.toml")) {
-            let mut db = HashMap::new();
-            self.time_played_ty_db
-                .iter()
-                .map(|(k, v)| db.insert(k, v.as_secs() as i64))
-                .last();
-            let _ = std::io::Write::write_fmt(
-                &mut file,
-                format_args!("{}", toml::to_string(&db).unwrap_or("42".to_owned())),
-            );
-        }
+    /// the index of the game `id` in `games`
+    fn index_of(&self, id: &str) -> Option<usize> {
+        self.games.iter().position(|a| a.id() == id)
     }
 
     fn update_log(&mut self) {
@@ -191,7 +235,8 @@ enum SGDBAsyncStatus {
     SearchQuery(String),
     ImageQuery(usize),
     ImageDownload(Vec<steamgriddb_api::images::Image>),
-    FinalImageDownload(steamgriddb_api::images::Image),
+    /// the banner of the game with the given id
+    FinalImageDownload(String, steamgriddb_api::images::Image),
     NoImage,
 }
 impl std::default::Default for SGDBAsyncStatus {
@@ -200,12 +245,6 @@ impl std::default::Default for SGDBAsyncStatus {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum ThemeType {
-    Light,
-    Dark,
-}
-
 #[derive(Debug, Clone)]
 pub enum Message {
     GameSelected(usize),
@@ -238,12 +277,73 @@ pub enum Message {
     ),
     SGDBAsyncFinalImageDownloadDone(Option<String>),
     DoNothing,
-    AddLogs(usize, String),
-    AddSender(usize, Sender<process_subscription::PSubInput>),
-    ProcessDied(usize),
+    // messages from running games are tagged with their id, see `Game::id`
+    AddLogs(String, String),
+    AddSender(String, Sender<process_subscription::PSubInput>),
+    /// the game exited, with its exit status if it has one and when it ended
+    ProcessDied(String, Option<i32>, std::time::SystemTime),
+    LaunchFailed(String, games::LaunchError),
+    ProcessesChanged(String, Vec<(sysinfo::Pid, String)>),
     ProcessWatcherClock,
     LogAction(iced::widget::text_editor::Action),
     SGDBAsyncNoImage,
+    CheckSelected,
+    /// the problems found with the game with this id
+    GameChecked(String, Vec<doctor::Problem>),
+    StatsPeriodChanged(stats::Period),
+    SortChanged(sort::Sorts),
+    ToggleSortOrder,
+    SearchChanged(String),
+    SetFilter(filter::Filter),
+    FocusSearch,
+    ToggleSidebar,
+    DeleteSelected,
+    ConfirmDelete,
+    CancelDelete,
+    DuplicateSelected,
+    ToggleHiddenSelected,
+    /// selects and runs the first game shown in the grid
+    LaunchTopResult,
+    /// opens the broken entry at this index in the default editor
+    OpenBrokenEntry(usize),
+    ResetBrokenRunner(usize),
+    RemoveBrokenBoxArt(usize),
+    /// tries to load the broken entry at this index again
+    ReloadBrokenEntry(usize),
+    /// the thumbnail of the box art (second field) of the game with the given id, decoded at the
+    /// width in the third field
+    ThumbnailLoaded(
+        String,
+        String,
+        u32,
+        Result<iced::widget::image::Handle, String>,
+    ),
+    /// the part of the games grid that is visible, if known
+    GridScrolled(Option<iced::widget::scrollable::Viewport>),
+    LibraryViewChanged(views::LibraryView),
+    /// sorts by a column of the list view, or reverses the order if it is already sorted by it
+    SortByColumn(sort::Sorts),
+    Input(input::Action),
+    ToggleCouchMode,
+    /// `theme.toml` was modified, and read again
+    ThemeFileChanged(Result<Box<theme::CustomTheme>, String>),
+    /// the box arts are being resized with the slider
+    CoverWidthChanged(f32),
+    SaveCoverWidth,
+    WheelScrolled(iced::mouse::ScrollDelta),
+    /// a while after the scroll with this number changed the width of the box arts
+    CoverWidthScrolled(u64),
+    ModifiersChanged(iced::keyboard::Modifiers),
+    WindowResized,
+    ScaleFactorChanged(f32),
+    ToggleWinetricksPicker,
+    WinetricksVerbsLoaded(Result<Vec<winetricks::Verb>, String>),
+    WinetricksSearchChanged(String),
+    DllOverrideDraftChanged(String),
+    /// adds the draft to these entries of `wine:dll_overrides`
+    AddDllOverride(Vec<String>),
+    RescanWineBuilds,
+    WineBuildsLoaded(Vec<wine_builds::WineBuild>),
 }
 
 impl MainGUI {
@@ -253,85 +353,71 @@ impl MainGUI {
     // type Flags = ();
 
     fn new() -> (Self, Command<Message>) {
-        let mut time_played_db = HashMap::new();
-        let mut time_played_ty_db = HashMap::new();
-        std::fs::read_to_string(DIRS.data_dir().join("times.toml"))
-            .unwrap_or_else(|e| {
-                log::error!("couldn't read playtime database : {e}");
-                String::new()
-            })
-            .parse::<toml::Table>()
-            .unwrap_or_else(|e| {
-                log::error!("couldn't read playtime database : {e}");
-                toml::Table::new()
-            })
-            .try_into::<HashMap<String, i64>>()
-            .unwrap_or_else(|e| {
-                log::error!("couldn't convert playtime database : {e}");
-                HashMap::new()
-            })
-            .drain()
-            .map(|(k, i)| {
-                time_played_db.insert(k, std::time::Duration::from_secs(i.try_into().unwrap_or(0)))
-            })
-            .last(); // here we just consume the iter such that our map does something
-        std::fs::read_to_string(DIRS.data_dir().join("times_ty.toml"))
-            .unwrap_or_else(|e| {
-                log::error!("couldn't read playtime database : {e}");
-                String::new()
-            })
-            .parse::<toml::Table>()
-            .unwrap_or_else(|e| {
-                log::error!("couldn't read playtime database : {e}");
-                toml::Table::new()
-            })
-            .try_into::<HashMap<String, i64>>()
-            .unwrap_or_else(|e| {
-                log::error!("couldn't convert playtime database : {e}");
-                HashMap::new()
-            })
-            .drain()
-            .map(|(k, i)| {
-                time_played_ty_db
-                    .insert(k, std::time::Duration::from_secs(i.try_into().unwrap_or(0)))
-            })
-            .last(); // here we just consume the iter such that our map does something
-        let mut games: Vec<_> = std::fs::read_dir(DIRS.config_dir().join("games"))
-            .unwrap()
-            .map(|a| {
-                Game::from_toml(
-                    &a.unwrap().path(),
-                    &DIRS.config_dir().join("settings.toml"),
-                    &time_played_db,
-                    &time_played_ty_db,
-                )
-            })
-            .collect();
-        //alphabetic sort
-        // games.sort_unstable_by_key(|a| a.name.clone());
-        sort::sort_none_selected(&mut games, sort::Sorts::Name.get_fn());
+        let mut playtime_db = playtime::PlaytimeDb::load();
+        let (mut games, broken) = games::load_games(&mut playtime_db);
+        let default_config = get_default_config_with_vals(&DIRS.config_dir().join("settings.toml"));
+        let (sort_alg, sort_descending) = sort::Sorts::from_config(&default_config);
+        let smart_collections = collections::smart_collections(&default_config);
+        let library_view = views::LibraryView::from_config(&default_config);
+        let theme_type = theme::ThemeType::from_config(&default_config);
+        let cover_width = views::cover_width_from_config(&default_config);
+        sort::sort_games(&mut games, sort_alg, sort_descending);
+        thumbnails::prune(games.iter().filter_map(|g| g.box_art.as_deref()));
 
+        let gui = MainGUI {
+            games,
+            selected: None,
+            default_config,
+            temp_settings: None,
+            grid_status: GridStatus::GamesGrid,
+            steam_grid_db: false,
+            sgdb_images: vec![],
+            sgdb_selected: None,
+            sgdb_query: String::new(),
+            sgdb_other_possibilities: vec![],
+            sgdb_async_status: SGDBAsyncStatus::default(),
+            playtime_db,
+            sort_alg,
+            sort_descending,
+            log: iced::widget::text_editor::Content::new(),
+            doctor_report: None,
+            runner_info: vec![],
+            stats_period: stats::Period::default(),
+            stats: stats::Stats::default(),
+            search: String::new(),
+            filter: filter::Filter::default(),
+            sidebar_open: true,
+            confirm_delete: false,
+            confirm_subcommand: None,
+            subcommand_refused: None,
+            smart_collections,
+            broken,
+            grid_viewport: None,
+            library_view,
+            couch_mode: false,
+            theme: theme::load(),
+            theme_type,
+            cover_width,
+            cover_width_scrolls: 0,
+            scale_factor: 1.,
+            thumbnail_width: 0,
+            modifiers: iced::keyboard::Modifiers::default(),
+            winetricks_verbs: None,
+            winetricks_picker: false,
+            winetricks_search: String::new(),
+            dll_override_draft: String::new(),
+            wine_builds: vec![],
+        };
+        let wine_builds = gui.load_wine_builds();
+        // the grid shows placeholders until the thumbnails are loaded, once the size they should
+        // be decoded at is known
         (
-            MainGUI {
-                games,
-                selected: None,
-                default_config: get_default_config_with_vals(
-                    &DIRS.config_dir().join("settings.toml"),
-                ),
-                temp_settings: None,
-                grid_status: GridStatus::GamesGrid,
-                steam_grid_db: false,
-                sgdb_images: vec![],
-                sgdb_selected: None,
-                sgdb_query: String::new(),
-                sgdb_other_possibilities: vec![],
-                sgdb_async_status: SGDBAsyncStatus::default(),
-                time_played_db,
-                time_played_ty_db,
-                sort_alg: sort::Sorts::Name,
-                log: iced::widget::text_editor::Content::new(),
-            },
-            iced::font::load(iced_fonts::NERD_FONT_BYTES).map(|_| Message::DoNothing),
+            gui,
+            Command::batch([
+                iced::font::load(iced_fonts::NERD_FONT_BYTES).map(|_| Message::DoNothing),
+                get_scale_factor(),
+                wine_builds,
+            ]),
             // Command::none(),
         )
     }
@@ -343,6 +429,9 @@ impl MainGUI {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::GameSelected(id) => {
+                if self.selected != Some(id) {
+                    self.doctor_report = None;
+                }
                 self.selected = Some(id);
                 self.update_log();
                 Command::none()
@@ -350,8 +439,12 @@ impl MainGUI {
             Message::RunSelected => {
                 if let Some(i) = self.selected {
                     self.games[i].current_log.clear();
+                    if let Err(e) = self.games[i].run() {
+                        self.games[i].current_log = e.to_string();
+                        self.update_log();
+                        return Command::none();
+                    }
                     self.games[i].time_started = Some(std::time::SystemTime::now());
-                    self.games[i].run();
                     if self.games[i].config.no_sleep_enabled {
                         match nosleep::NoSleep::new() {
                             Ok(mut ns) => {
@@ -377,6 +470,7 @@ impl MainGUI {
                 } else {
                     self.grid_status = GridStatus::GamesGrid;
                 }
+                self.read_runner_info();
                 Command::none()
             }
             Message::SettingChanged(s1, s2) => {
@@ -396,18 +490,33 @@ impl MainGUI {
                 Command::none()
             }
             Message::ApplySettings | Message::ApplyCloseSettings => {
+                let thumbnails;
                 match self.grid_status {
                     GridStatus::GamesGrid => unreachable!(),
                     GridStatus::Logs => unreachable!(),
+                    GridStatus::Stats => unreachable!(),
+                    GridStatus::BrokenEntries => unreachable!(),
                     GridStatus::GamesSettings => {
+                        self.doctor_report = None;
                         let path = self.games[self.selected.unwrap()].path_to_toml.clone();
-                        self.games[self.selected.unwrap()] =
-                            self.temp_settings.as_ref().unwrap().clone().into_game(
-                                &DIRS.config_dir().join("settings.toml"),
-                                path.clone(),
-                                &self.time_played_db,
-                                &self.time_played_ty_db,
-                            );
+                        // winetricks is only run again when the game asks for other verbs
+                        let verbs = |cfg: &Cfg| match cfg.0.get("wine:winetricks_verbs") {
+                            Some(CValue::StrArr(a)) => a.clone(),
+                            _ => vec![],
+                        };
+                        let verbs_changed = verbs(&Cfg::from_toml(&path))
+                            != verbs(self.temp_settings.as_ref().unwrap());
+                        match self.temp_settings.as_ref().unwrap().clone().into_game(
+                            &DIRS.config_dir().join("settings.toml"),
+                            path.clone(),
+                            &self.playtime_db,
+                        ) {
+                            Ok(g) => thumbnails = self.replace_game(self.selected.unwrap(), g),
+                            Err(e) => {
+                                log::error!("couldn't apply the settings : {e}");
+                                return Command::none();
+                            }
+                        }
 
                         let to_write = self.temp_settings.as_ref().unwrap().to_toml();
                         use std::io::prelude::*;
@@ -415,6 +524,14 @@ impl MainGUI {
                             .unwrap()
                             .write_all(to_write.as_bytes())
                             .unwrap();
+                        self.sort();
+                        self.read_runner_info();
+
+                        if verbs_changed {
+                            if let Some(setup) = self.run_setup(self.selected.unwrap()) {
+                                return Command::batch([thumbnails, setup]);
+                            }
+                        }
                     }
                     GridStatus::GlobalSettings => {
                         let path = DIRS
@@ -434,19 +551,37 @@ impl MainGUI {
                             &DIRS.config_dir().join("settings.toml"),
                         );
 
+                        self.cover_width = views::cover_width_from_config(&self.default_config);
+                        // the folders searched for wine builds may have changed
+                        let mut tasks = vec![self.resize_thumbnails(), self.load_wine_builds()];
                         for i in 0..self.games.len() {
-                            self.games[i] = self.games[i].bare_config.clone().into_game(
+                            match self.games[i].bare_config.clone().into_game(
                                 &DIRS.config_dir().join("settings.toml"),
                                 self.games[i].path_to_toml.clone(),
-                                &self.time_played_db,
-                                &self.time_played_ty_db,
-                            );
+                                &self.playtime_db,
+                            ) {
+                                Ok(g) => tasks.push(self.replace_game(i, g)),
+                                Err(e) => {
+                                    log::error!("couldn't reload {} : {e}", self.games[i].name)
+                                }
+                            }
+                        }
+                        thumbnails = Command::batch(tasks);
+                        (self.sort_alg, self.sort_descending) =
+                            sort::Sorts::from_config(&self.default_config);
+                        self.library_view = views::LibraryView::from_config(&self.default_config);
+                        self.theme_type = theme::ThemeType::from_config(&self.default_config);
+                        self.smart_collections =
+                            collections::smart_collections(&self.default_config);
+                        if let Some(collections::Group::Smart(_)) = self.filter.group {
+                            self.filter.group = None;
                         }
+                        self.sort();
                     }
                     GridStatus::AddGame => {
-                        let random_id: u16 = rand::random();
-                        let name = make_path_proof(
-                            self.temp_settings
+                        let path = new_game_path(
+                            &self
+                                .temp_settings
                                 .as_ref()
                                 .unwrap()
                                 .0
@@ -454,32 +589,43 @@ impl MainGUI {
                                 .map(|a| a.as_string())
                                 .unwrap_or_default(),
                         );
-                        let path = DIRS
-                            .config_dir()
-                            .join("games")
-                            .join(random_id.to_string() + &name[..] + ".toml");
                         let cfg = self.temp_settings.as_mut().unwrap();
-                        self.games.push(cfg.clone().into_game(
+                        match cfg.clone().into_game(
                             &DIRS.config_dir().join("settings.toml"),
                             path.clone(),
-                            &self.time_played_db,
-                            &self.time_played_ty_db,
-                        ));
+                            &self.playtime_db,
+                        ) {
+                            Ok(g) => self.games.push(g),
+                            Err(e) => {
+                                log::error!("couldn't add the game : {e}");
+                                return Command::none();
+                            }
+                        }
+                        thumbnails = self.load_thumbnails([self.games.len() - 1]);
 
                         let to_write = self.temp_settings.as_ref().unwrap().to_toml();
                         use std::io::prelude::*;
-                        std::fs::File::create(path)
+                        std::fs::File::create(&path)
                             .unwrap()
                             .write_all(to_write.as_bytes())
                             .unwrap();
 
-                        self.sort(self.sort_alg.get_fn());
+                        if let Some(g) = self.games.last_mut() {
+                            g.date_added = Some(std::time::SystemTime::now());
+                        }
+                        self.sort();
+
+                        if let Some(i) = self.games.iter().position(|g| g.path_to_toml == path) {
+                            if let Some(setup) = self.run_setup(i) {
+                                return Command::batch([thumbnails, setup]);
+                            }
+                        }
                     }
                 }
                 if let Message::ApplyCloseSettings = message {
                     self.grid_status = GridStatus::GamesGrid;
                 }
-                Command::none()
+                thumbnails
             }
             Message::ToggleGlobalSettings => {
                 if let GridStatus::GamesGrid = self.grid_status {
@@ -508,6 +654,8 @@ impl MainGUI {
                         _ => {}
                     }
                     self.grid_status = status;
+                    self.read_runner_info();
+                    self.compute_stats();
                 };
                 Command::none()
             }
@@ -536,8 +684,24 @@ impl MainGUI {
             }
             Message::RunSubcommandSelected(s) => {
                 if let Some(i) = self.selected {
+                    // running it again from the dialog confirms it
+                    if self.confirm_subcommand.as_ref() != Some(&s)
+                        && self.games[i].runner.confirm_subcommand(&s).is_some()
+                    {
+                        self.subcommand_refused = self.refuse_subcommand(i, &s);
+                        self.confirm_subcommand = Some(s);
+                        return Command::none();
+                    }
+                    if let Some(e) = self.refuse_subcommand(i, &s) {
+                        log::error!("not running {s} : {e}");
+                        return Command::none();
+                    }
+                    self.confirm_subcommand = None;
                     self.games[i].current_log.clear();
-                    self.games[i].run_subcommand(s);
+                    if let Err(e) = self.games[i].run_subcommand(s) {
+                        self.games[i].current_log = e.to_string();
+                        self.update_log();
+                    }
                 }
                 Command::none()
             }
@@ -591,8 +755,14 @@ impl MainGUI {
             }
             Message::ApplySGDB => {
                 if let Some(sel) = self.sgdb_selected {
+                    let id = self
+                        .temp_settings
+                        .as_ref()
+                        .and_then(|a| a.0.get("id"))
+                        .map(|a| a.as_string())
+                        .unwrap_or_default();
                     self.sgdb_async_status =
-                        SGDBAsyncStatus::FinalImageDownload(self.sgdb_images[sel].0.clone())
+                        SGDBAsyncStatus::FinalImageDownload(id, self.sgdb_images[sel].0.clone())
                 }
                 Command::none()
             }
@@ -649,11 +819,419 @@ impl MainGUI {
                 self.sgdb_async_status = SGDBAsyncStatus::NoImage;
                 Command::none()
             }
+            Message::CheckSelected => {
+                let Some(i) = self.selected else {
+                    return Command::none();
+                };
+                // the checks run programs and read files, so they use a copy of the game
+                let g = &self.games[i];
+                let (id, cfg, path) = (g.id(), g.bare_config.clone(), g.path_to_toml.clone());
+                Command::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            cfg.into_game(
+                                &DIRS.config_dir().join("settings.toml"),
+                                path,
+                                &playtime::PlaytimeDb::default(),
+                            )
+                            .map(|g| doctor::check_game(&g))
+                            .unwrap_or_else(|e| vec![doctor::Problem::error(e)])
+                        })
+                        .await
+                        .unwrap_or_default()
+                    },
+                    move |report| Message::GameChecked(id.clone(), report),
+                )
+            }
+            Message::GameChecked(id, report) => {
+                if self.selected.is_some() && self.selected == self.index_of(&id) {
+                    self.doctor_report = Some(report);
+                }
+                Command::none()
+            }
+            Message::SortChanged(sort) => {
+                self.sort_alg = sort;
+                self.sort();
+                self.save_sort();
+                Command::none()
+            }
+            Message::ToggleSortOrder => {
+                self.sort_descending = !self.sort_descending;
+                self.sort();
+                self.save_sort();
+                Command::none()
+            }
+            Message::SortByColumn(sort) => {
+                if self.sort_alg == sort {
+                    self.sort_descending = !self.sort_descending;
+                } else {
+                    self.sort_alg = sort;
+                    self.sort_descending = false;
+                }
+                self.sort();
+                self.save_sort();
+                Command::none()
+            }
+            Message::Input(action) => self.handle_input(action),
+            Message::ToggleCouchMode => {
+                self.couch_mode = !self.couch_mode;
+                self.grid_viewport = None;
+                self.grid_status = GridStatus::GamesGrid;
+                let mode = if self.couch_mode {
+                    iced::window::Mode::Fullscreen
+                } else {
+                    iced::window::Mode::Windowed
+                };
+                Command::batch([
+                    iced::window::get_latest()
+                        .and_then(move |id| iced::window::change_mode(id, mode)),
+                    self.resize_thumbnails(),
+                ])
+            }
+            Message::ThemeFileChanged(t) => {
+                match t {
+                    Ok(t) => self.theme = *t,
+                    Err(e) => log::error!("couldn't reload \"%data_dir%/theme.toml\" : {e}"),
+                }
+                Command::none()
+            }
+            Message::LibraryViewChanged(view) => {
+                self.library_view = view;
+                // the rows built depend on the layout
+                self.grid_viewport = None;
+                self.save_view();
+                Command::none()
+            }
+            Message::SearchChanged(search) => {
+                self.search = search;
+                Command::none()
+            }
+            Message::SetFilter(filter) => {
+                self.filter = filter;
+                Command::none()
+            }
+            Message::ThumbnailLoaded(id, box_art, width, thumbnail) => {
+                // the box arts were resized since, a larger or smaller thumbnail is on the way
+                if width != self.thumbnail_width {
+                    return Command::none();
+                }
+                let Some(i) = self.index_of(&id) else {
+                    return Command::none();
+                };
+                // the box art may have changed in the meantime
+                if self.games[i].box_art.as_ref() != Some(&box_art) {
+                    return Command::none();
+                }
+                match thumbnail {
+                    Ok(t) => self.games[i].image = t,
+                    Err(e) if self.games[i].is_running => log::error!("{e}"),
+                    Err(error) => {
+                        let selected = self.selected.map(|a| self.games[a].path_to_toml.clone());
+                        let game = self.games.remove(i);
+                        self.selected = selected
+                            .and_then(|p| self.games.iter().position(|a| a.path_to_toml == p));
+                        if self.selected.is_none()
+                            && matches!(
+                                self.grid_status,
+                                GridStatus::GamesSettings | GridStatus::Logs
+                            )
+                        {
+                            self.grid_status = GridStatus::GamesGrid;
+                        }
+                        log::error!("couldn't load {:?} : {error}", game.path_to_toml);
+                        self.broken.push(games::BrokenEntry {
+                            path: game.path_to_toml,
+                            error,
+                        });
+                    }
+                }
+                Command::none()
+            }
+            Message::GridScrolled(viewport) => {
+                self.grid_viewport = viewport;
+                Command::none()
+            }
+            Message::CoverWidthChanged(width) => {
+                self.cover_width = width;
+                Command::none()
+            }
+            Message::SaveCoverWidth => {
+                self.save_cover_width();
+                self.resize_thumbnails()
+            }
+            Message::WheelScrolled(delta) => {
+                let grid_shown = self.couch_mode || self.library_view == views::LibraryView::Grid;
+                if !self.modifiers.command()
+                    || self.grid_status != GridStatus::GamesGrid
+                    || !grid_shown
+                {
+                    return Command::none();
+                }
+                let y = match delta {
+                    iced::mouse::ScrollDelta::Lines { y, .. } => y * 20.,
+                    iced::mouse::ScrollDelta::Pixels { y, .. } => y,
+                };
+                self.cover_width = (self.cover_width + y)
+                    .clamp(*views::COVER_WIDTHS.start(), *views::COVER_WIDTHS.end());
+                // saving and decoding the thumbnails again waits for the scrolling to stop
+                self.cover_width_scrolls += 1;
+                let scroll = self.cover_width_scrolls;
+                Command::perform(
+                    tokio::time::sleep(std::time::Duration::from_millis(300)),
+                    move |_| Message::CoverWidthScrolled(scroll),
+                )
+            }
+            Message::CoverWidthScrolled(scroll) => {
+                if scroll != self.cover_width_scrolls {
+                    return Command::none();
+                }
+                self.update(Message::SaveCoverWidth)
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                Command::none()
+            }
+            Message::WindowResized => {
+                // the grid builds every row until the scrollable reports its new size
+                self.grid_viewport = None;
+                // the window may have been moved to a screen with another scale factor
+                get_scale_factor()
+            }
+            Message::ToggleWinetricksPicker => {
+                self.winetricks_picker = !self.winetricks_picker;
+                if self.winetricks_picker && !matches!(self.winetricks_verbs, Some(Ok(_))) {
+                    return self.load_winetricks_verbs();
+                }
+                Command::none()
+            }
+            Message::WinetricksVerbsLoaded(verbs) => {
+                if let Err(e) = &verbs {
+                    log::error!("couldn't list the winetricks verbs : {e}");
+                }
+                self.winetricks_verbs = Some(verbs);
+                Command::none()
+            }
+            Message::WinetricksSearchChanged(search) => {
+                self.winetricks_search = search;
+                Command::none()
+            }
+            Message::DllOverrideDraftChanged(draft) => {
+                self.dll_override_draft = draft;
+                Command::none()
+            }
+            Message::AddDllOverride(mut entries) => {
+                // these would end the entry, several dlls being separated by commas
+                let dll = self.dll_override_draft.replace(['=', ';'], "");
+                if dll.trim().is_empty() {
+                    return Command::none();
+                }
+                entries.push(dll_overrides::entry(
+                    dll.trim(),
+                    dll_overrides::Mode::NativeBuiltin,
+                ));
+                self.dll_override_draft.clear();
+                self.update(Message::SettingChanged(
+                    "wine:dll_overrides".to_owned(),
+                    CValue::StrArr(entries),
+                ))
+            }
+            Message::RescanWineBuilds => self.load_wine_builds(),
+            Message::WineBuildsLoaded(builds) => {
+                log::info!("found {} wine builds", builds.len());
+                self.wine_builds = builds;
+                Command::none()
+            }
+            Message::ScaleFactorChanged(scale_factor) => {
+                self.scale_factor = scale_factor;
+                self.resize_thumbnails()
+            }
+            Message::OpenBrokenEntry(i) => {
+                #[cfg(unix)]
+                let opener = "xdg-open";
+                #[cfg(windows)]
+                let opener = "notepad";
+                if let Err(e) = std::process::Command::new(opener)
+                    .arg(&self.broken[i].path)
+                    .spawn()
+                {
+                    log::error!("couldn't open {:?} : {e}", self.broken[i].path);
+                }
+                Command::none()
+            }
+            Message::ResetBrokenRunner(i) | Message::RemoveBrokenBoxArt(i) => {
+                let key = if let Message::ResetBrokenRunner(_) = message {
+                    "runner"
+                } else {
+                    "box_art"
+                };
+                if let Err(e) = config::remove_metadata_key(&self.broken[i].path, key) {
+                    self.broken[i].error = e;
+                    return Command::none();
+                }
+                self.update(Message::ReloadBrokenEntry(i))
+            }
+            Message::ReloadBrokenEntry(i) => {
+                match games::load_game(&self.broken[i].path, &self.games, &mut self.playtime_db) {
+                    Ok(g) => {
+                        self.broken.remove(i);
+                        self.games.push(g);
+                        let thumbnail = self.load_thumbnails([self.games.len() - 1]);
+                        self.sort();
+                        if self.broken.is_empty() && self.grid_status == GridStatus::BrokenEntries {
+                            self.grid_status = GridStatus::GamesGrid;
+                        }
+                        return thumbnail;
+                    }
+                    Err(e) => self.broken[i].error = e,
+                }
+                Command::none()
+            }
+            Message::DeleteSelected => {
+                self.confirm_delete = self.selected.is_some_and(|i| !self.games[i].is_running);
+                Command::none()
+            }
+            Message::CancelDelete => {
+                self.confirm_delete = false;
+                self.confirm_subcommand = None;
+                Command::none()
+            }
+            Message::ConfirmDelete => {
+                self.confirm_delete = false;
+                if let Some(i) = self.selected {
+                    // its process would be left behind, and its session never recorded
+                    if self.games[i].is_running {
+                        log::error!("can't delete {}, it is running", self.games[i].name);
+                        return Command::none();
+                    }
+                    if let Err(e) = library::move_to_trash(&self.games[i].path_to_toml) {
+                        log::error!(
+                            "couldn't move {:?} to the trash : {e}",
+                            self.games[i].path_to_toml
+                        );
+                        return Command::none();
+                    }
+                    let game = self.games.remove(i);
+                    if let Some(b) = game.box_art.as_ref().filter(|a| {
+                        library::is_managed_banner(a)
+                            && !self.games.iter().any(|g| g.box_art.as_ref() == Some(a))
+                    }) {
+                        if let Err(e) = library::move_to_trash(std::path::Path::new(b)) {
+                            log::error!("couldn't move banner {b} to the trash : {e}");
+                        }
+                    }
+                    if game.log_path().exists() {
+                        if let Err(e) = library::move_to_trash(&game.log_path()) {
+                            log::error!(
+                                "couldn't move the logs of {} to the trash : {e}",
+                                game.id()
+                            );
+                        }
+                    }
+                    // the sessions are kept, so that restoring the toml from the trash brings
+                    // the playtime back
+                    self.selected = None;
+                    self.doctor_report = None;
+                    self.temp_settings = None;
+                    self.grid_status = GridStatus::GamesGrid;
+                }
+                Command::none()
+            }
+            Message::DuplicateSelected => {
+                if let Some(i) = self.selected {
+                    let mut cfg = self.games[i].bare_config.clone();
+                    let name = self.games[i].name.clone() + " (copy)";
+                    cfg.0.insert("name".to_owned(), CValue::Str(name.clone()));
+                    cfg.0.insert("id".to_owned(), CValue::Str(games::new_id()));
+                    if let Some(b) = self.games[i]
+                        .box_art
+                        .as_ref()
+                        .filter(|a| library::is_managed_banner(a))
+                    {
+                        match library::copy_banner(b) {
+                            Ok(p) => {
+                                cfg.0.insert(
+                                    "box_art".to_owned(),
+                                    CValue::PickFile(p.to_string_lossy().into_owned()),
+                                );
+                            }
+                            Err(e) => log::error!("couldn't copy banner {b} : {e}"),
+                        }
+                    }
+                    let path = new_game_path(&name);
+                    if let Err(e) = std::fs::write(&path, cfg.to_toml()) {
+                        log::error!("couldn't write {path:?} : {e}");
+                        return Command::none();
+                    }
+                    match cfg.into_game(
+                        &DIRS.config_dir().join("settings.toml"),
+                        path,
+                        &self.playtime_db,
+                    ) {
+                        Ok(g) => self.games.push(g),
+                        Err(e) => {
+                            log::error!("couldn't load the copy : {e}");
+                            return Command::none();
+                        }
+                    }
+                    let thumbnail = self.load_thumbnails([self.games.len() - 1]);
+                    self.selected = Some(self.games.len() - 1);
+                    self.doctor_report = None;
+                    self.grid_status = GridStatus::GamesGrid;
+                    self.sort();
+                    return thumbnail;
+                }
+                Command::none()
+            }
+            Message::ToggleHiddenSelected => {
+                if let Some(i) = self.selected {
+                    let hidden = !self.games[i].hidden;
+                    let mut cfg = self.games[i].bare_config.clone();
+                    cfg.0.insert("hidden".to_owned(), CValue::Bool(hidden));
+                    if let Some(t) = self.temp_settings.as_mut() {
+                        if self.grid_status == GridStatus::GamesSettings {
+                            t.0.insert("hidden".to_owned(), CValue::Bool(hidden));
+                        }
+                    }
+                    let path = self.games[i].path_to_toml.clone();
+                    if let Err(e) = std::fs::write(&path, cfg.to_toml()) {
+                        log::error!("couldn't write {path:?} : {e}");
+                        return Command::none();
+                    }
+                    self.games[i].bare_config = cfg;
+                    self.games[i].hidden = hidden;
+                }
+                Command::none()
+            }
+            Message::ToggleSidebar => {
+                self.sidebar_open = !self.sidebar_open;
+                Command::none()
+            }
+            Message::FocusSearch => {
+                self.grid_status = GridStatus::GamesGrid;
+                iced::widget::text_input::focus(ui::SEARCH_ID.clone())
+            }
+            Message::LaunchTopResult => {
+                if let Some(&i) = filter::visible(&self.games, &self.search, &self.filter).first() {
+                    let _ = self.update(Message::GameSelected(i));
+                    if !self.games[i].is_running {
+                        return self.update(Message::RunSelected);
+                    }
+                }
+                Command::none()
+            }
+            Message::StatsPeriodChanged(period) => {
+                self.stats_period = period;
+                self.compute_stats();
+                Command::none()
+            }
             Message::DoNothing => {
                 //yep, you guessed it, we do nothing here
                 Command::none()
             }
-            Message::AddLogs(i, logs) => {
+            Message::AddLogs(id, logs) => {
+                let Some(i) = self.index_of(&id) else {
+                    return Command::none();
+                };
                 self.games[i].current_log += &logs[..];
                 if i == self.selected.unwrap_or(usize::MAX) {
                     use iced::widget::text_editor::{Action, Edit, Motion};
@@ -665,43 +1243,67 @@ impl MainGUI {
                 // self.update_log();
                 Command::none()
             }
-            Message::AddSender(i, sender) => {
+            Message::AddSender(id, sender) => {
+                let Some(i) = self.index_of(&id) else {
+                    return Command::none();
+                };
                 self.games[i].psub_sender = Some(sender);
                 Command::none()
             }
-            Message::ProcessDied(i) => {
-                if let Some(when) = self.games[i].time_started {
-                    log::info!(
-                        "process {i} died: previous playtime: {:?}",
-                        self.games[i].time_played
-                    );
-                    self.games[i].time_played += std::time::SystemTime::now()
-                        .duration_since(when)
-                        .unwrap_or(std::time::Duration::new(0, 0));
+            Message::ProcessDied(id, exit_status, end) => {
+                let Some(i) = self.index_of(&id) else {
+                    return Command::none();
+                };
+                if let Some(start) = self.games[i].time_started.take() {
+                    self.playtime_db.record(playtime::Session {
+                        id: id.clone(),
+                        start,
+                        end,
+                        runner: self.games[i].runner_id.clone(),
+                        exit_status,
+                        legacy: false,
+                    });
+                    self.games[i].playtime = self.playtime_db.stats(&id);
                     log::info!(
-                        "process {i} died: new playtime: {:?}",
-                        self.games[i].time_played
-                    );
-                    self.games[i].time_played_this_year += std::time::SystemTime::now()
-                        .duration_since(when)
-                        .unwrap_or(std::time::Duration::new(0, 0));
-                    self.time_played_db.insert(
-                        self.games[i]
-                            .path_to_toml
-                            .file_name()
-                            .unwrap_or_default()
-                            .to_str()
-                            .unwrap_or_default()
-                            .to_owned(),
-                        self.games[i].time_played,
+                        "game {id} died: new playtime: {:?}",
+                        self.games[i].playtime.total
                     );
-                    self.games[i].time_started = None;
-                    self.update_db();
                 } else {
-                    log::info!("process {i} died: no playtime added")
+                    log::info!("game {id} died: no playtime added")
+                }
+                if let Err(e) = std::fs::write(self.games[i].log_path(), &self.games[i].current_log)
+                {
+                    log::error!("couldn't save the logs of game {id} : {e}");
                 }
                 self.games[i].no_sleep = None;
                 self.games[i].psub_sender = None;
+                self.games[i].managed_processes.clear();
+                self.games[i].is_running = false;
+                // e.g. the prefix was set up by a subcommand
+                self.read_runner_info();
+                self.compute_stats();
+                Command::none()
+            }
+            Message::ProcessesChanged(id, processes) => {
+                let Some(i) = self.index_of(&id) else {
+                    return Command::none();
+                };
+                self.games[i].managed_processes = processes;
+                Command::none()
+            }
+            Message::LaunchFailed(id, e) => {
+                let Some(i) = self.index_of(&id) else {
+                    return Command::none();
+                };
+                log::error!("couldn't launch {} : {e}", self.games[i].name);
+                self.games[i].current_log += &e.to_string();
+                if Some(i) == self.selected {
+                    self.update_log();
+                }
+                self.games[i].launch_error = Some(e);
+                self.games[i].time_started = None;
+                self.games[i].no_sleep = None;
+                self.games[i].psub_sender = None;
                 self.games[i].is_running = false;
                 Command::none()
             }
@@ -814,39 +1416,51 @@ impl MainGUI {
                     iced::Subscription::none()
                 }
             }
-            SGDBAsyncStatus::FinalImageDownload(image) => iced::Subscription::run_with_id(
-                image.id,
-                iced::futures::stream::unfold(image.clone(), move |image| async move {
-                    let url = image.clone().url;
-                    let name = image.clone().id.to_string()
-                        + match image.clone().mime {
-                            steamgriddb_api::images::MimeTypes::Default(tp) => match tp {
-                                steamgriddb_api::query_parameters::MimeType::Png => ".png",
-                                steamgriddb_api::query_parameters::MimeType::Jpeg => ".jpeg",
-                                steamgriddb_api::query_parameters::MimeType::Webp => ".webp",
-                            },
-                            _ => unreachable!(),
-                        };
+            SGDBAsyncStatus::FinalImageDownload(game_id, image) => {
+                let game_id = game_id.clone();
+                iced::Subscription::run_with_id(
+                    image.id,
+                    iced::futures::stream::unfold(image.clone(), move |image| {
+                        let game_id = game_id.clone();
+                        async move {
+                            let url = image.clone().url;
+                            let name = game_id
+                                + "-"
+                                + &image.clone().id.to_string()
+                                + match image.clone().mime {
+                                    steamgriddb_api::images::MimeTypes::Default(tp) => match tp {
+                                        steamgriddb_api::query_parameters::MimeType::Png => ".png",
+                                        steamgriddb_api::query_parameters::MimeType::Jpeg => {
+                                            ".jpeg"
+                                        }
+                                        steamgriddb_api::query_parameters::MimeType::Webp => {
+                                            ".webp"
+                                        }
+                                    },
+                                    _ => unreachable!(),
+                                };
 
-                    let path = if let Ok(resp) = reqwest::get(url).await {
-                        if resp.status() == reqwest::StatusCode::OK {
-                            use std::io::prelude::*;
-                            let path = DIRS.data_dir().join("banners").join(name);
-                            std::fs::File::create(path.clone())
-                                .unwrap()
-                                .write_all(&resp.bytes().await.unwrap())
-                                .unwrap();
-                            path.to_str().map(|a| a.to_owned())
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
+                            let path = if let Ok(resp) = reqwest::get(url).await {
+                                if resp.status() == reqwest::StatusCode::OK {
+                                    use std::io::prelude::*;
+                                    let path = DIRS.data_dir().join("banners").join(name);
+                                    std::fs::File::create(path.clone())
+                                        .unwrap()
+                                        .write_all(&resp.bytes().await.unwrap())
+                                        .unwrap();
+                                    path.to_str().map(|a| a.to_owned())
+                                } else {
+                                    None
+                                }
+                            } else {
+                                None
+                            };
 
-                    Some((Message::SGDBAsyncFinalImageDownloadDone(path), image))
-                }),
-            ),
+                            Some((Message::SGDBAsyncFinalImageDownloadDone(path), image))
+                        }
+                    }),
+                )
+            }
         };
         let mono_clock = iced::time::every(iced::time::Duration::from_millis(
             if let GridStatus::Logs = self.grid_status {
@@ -856,45 +1470,353 @@ impl MainGUI {
             },
         ))
         .map(|_| Message::MonotonicClock);
+        let grace = std::time::Duration::from_secs_f64(
+            self.default_config
+                .get("launcher:kill_grace_period")
+                .unwrap()
+                .1
+                .as_string()
+                .parse::<f64>()
+                .unwrap_or(5.)
+                .max(0.),
+        );
         let mut running_processes = Vec::new();
-        for (i, g) in self.games.iter().enumerate() {
+        for g in self.games.iter() {
             if g.is_running {
-                running_processes.push(process_subscription::get_psub(i, g.cmd_to_run.clone()).map(
-                    |input| match input {
-                        process_subscription::Event::Ready(i, sender) => {
-                            Message::AddSender(i, sender)
-                        }
-                        process_subscription::Event::GotLogs(i, logs) => Message::AddLogs(i, logs),
-                        process_subscription::Event::ProcessEnded(i) => Message::ProcessDied(i),
-                    },
-                ))
+                // subcommands (configuration tools...) don't hand over to the game
+                let rule = g.time_started.and(g.config.tracked_process.clone());
+                running_processes.push(
+                    process_subscription::get_psub(g.id(), g.cmd_to_run.clone(), grace, rule).map(
+                        |input| match input {
+                            process_subscription::Event::Ready(i, sender) => {
+                                Message::AddSender(i, sender)
+                            }
+                            process_subscription::Event::GotLogs(i, logs) => {
+                                Message::AddLogs(i, logs)
+                            }
+                            process_subscription::Event::ProcessEnded(i, status, end) => {
+                                Message::ProcessDied(i, status, end)
+                            }
+                            process_subscription::Event::LaunchFailed(i, e) => {
+                                Message::LaunchFailed(i, e)
+                            }
+                            process_subscription::Event::Processes(i, processes) => {
+                                Message::ProcessesChanged(i, processes)
+                            }
+                        },
+                    ),
+                )
             }
         }
+        running_processes.push(iced::keyboard::on_key_press(|key, modifiers| {
+            match key.as_ref() {
+                iced::keyboard::Key::Character("f") if modifiers.command() => {
+                    Some(Message::FocusSearch)
+                }
+                _ => input::from_key(&key, modifiers).map(Message::Input),
+            }
+        }));
+        running_processes.push(input::gamepad().map(Message::Input));
+        running_processes.push(theme::watch().map(Message::ThemeFileChanged));
+        running_processes.push(iced::window::resize_events().map(|_| Message::WindowResized));
+        running_processes.push(iced::event::listen_with(|event, _, _| match event {
+            iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(m)) => {
+                Some(Message::ModifiersChanged(m))
+            }
+            // Ctrl+scroll resizes the box arts
+            iced::Event::Mouse(iced::mouse::Event::WheelScrolled { delta }) => {
+                Some(Message::WheelScrolled(delta))
+            }
+            _ => None,
+        }));
         running_processes.push(mono_clock);
         running_processes.push(sgdb_async);
         iced::Subscription::batch(running_processes)
     }
 
-    fn theme(&self) -> iced::Theme {
-        iced::theme::Theme::custom_with_fn(
-            // So here we should really invert primary strong and primary base (somehow)
-            "that cute pink theme".to_owned(),
-            Palette {
-                background: color!(0x303446), // Base
-                text: color!(0xc6d0f5),       // Text
-                primary: color!(0xFFABFF),    // Not Blue
-                success: color!(0xa6d189),    // Green
-                danger: color!(0xe78284),     // Red
-            },
-            |pal| {
-                let mut out = iced::theme::palette::Extended::generate(pal);
-                out.primary.strong.color = out.primary.base.color;
-                out.primary.base.color = mul_color(out.primary.base.color, 4. / 5.);
+    /// why the subcommand `s` of the game at `i` can't be run, e.g. deleting a wineprefix other
+    /// games use
+    fn refuse_subcommand(&self, i: usize, s: &str) -> Option<String> {
+        if s != "delete prefix" {
+            return None;
+        }
+        let g = &self.games[i];
+        // the prefix being deleted might not be the one of the settings shown
+        if self.grid_status == GridStatus::GamesSettings
+            && self.temp_settings.as_ref().map(|a| a.to_toml()) != Some(g.bare_config.to_toml())
+        {
+            return Some(
+                "The settings of the game have unsaved changes, apply or cancel them first."
+                    .to_owned(),
+            );
+        }
+        let others = g
+            .runner
+            .wineprefix()
+            .map(|p| library::prefix_users(&self.games, &p))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|a| a.uuid != g.uuid)
+            .map(|a| &a.name[..])
+            .collect::<Vec<_>>();
+        (!others.is_empty()).then(|| {
+            format!(
+                "The wineprefix is also used by {}, it can't be deleted.",
+                others.join(", ")
+            )
+        })
+    }
+
+    /// reads what the runner of the selected game set up, while its settings are shown
+    fn read_runner_info(&mut self) {
+        self.runner_info = match (self.grid_status, self.selected) {
+            (GridStatus::GamesSettings, Some(i)) => self.games[i].runner.info(),
+            _ => vec![],
+        };
+    }
+
+    /// computes the stats of the session history while they are shown
+    fn compute_stats(&mut self) {
+        if self.grid_status == GridStatus::Stats {
+            self.stats = stats::compute(&self.playtime_db, &self.games, self.stats_period);
+        }
+    }
+
+    /// runs what the runner of the game at `i` needs to be set up (e.g. its wineprefix), with the
+    /// progress shown in the logs
+    fn run_setup(&mut self, i: usize) -> Option<Command<Message>> {
+        if self.games[i].is_running {
+            return None;
+        }
+        let setup = self.games[i].runner.setup_subcommand()?;
+        let selected = self.update(Message::GameSelected(i));
+        let run = self.update(Message::RunSubcommandSelected(setup));
+        self.grid_status = GridStatus::Logs;
+        Some(Command::batch([selected, run]))
+    }
+
+    /// sorts the games according to the current sort while keeping the selected game selected
+    pub fn sort(&mut self) {
+        let selected = self.selected.map(|i| self.games[i].path_to_toml.clone());
+        sort::sort_games(&mut self.games, self.sort_alg, self.sort_descending);
+        if let Some(p) = selected {
+            self.selected = self.games.iter().position(|a| a.path_to_toml == p);
+        }
+    }
+
+    /// remembers the current sort in the global settings
+    pub fn save_sort(&mut self) {
+        self.save_settings(&[
+            ("launcher:sort", self.sort_alg.to_config()),
+            (
+                "launcher:sort_descending",
+                CValue::Bool(self.sort_descending),
+            ),
+        ]);
+    }
+
+    /// remembers the current view in the global settings
+    pub fn save_view(&mut self) {
+        self.save_settings(&[("launcher:view", self.library_view.to_config())]);
+    }
+
+    /// remembers the width of the box arts in the global settings
+    pub fn save_cover_width(&mut self) {
+        self.save_settings(&[(
+            "launcher:cover_width",
+            CValue::Str(self.cover_width.round().to_string()),
+        )]);
+    }
+
+    /// sets `values` in the global settings, and reloads the defaults the games fall back on
+    fn save_settings(&mut self, values: &[(&str, CValue)]) {
+        let path = DIRS.config_dir().join("settings.toml");
+        if let Err(e) = config::save_settings(&path, values) {
+            log::error!("couldn't save the settings : {e}");
+        }
+        self.default_config = get_default_config_with_vals(&path);
+    }
+
+    /// the colours of the light or dark theme, depending on the settings
+    pub fn colors(&self) -> &theme::Colors {
+        self.theme.colors(self.theme_type)
+    }
+
+    /// decodes every thumbnail again if the box arts are now shown at a different size
+    pub fn resize_thumbnails(&mut self) -> Command<Message> {
+        let width = thumbnails::width_for(ui::cover_size(self).width * self.scale_factor);
+        if width == self.thumbnail_width {
+            return Command::none();
+        }
+        self.thumbnail_width = width;
+        self.load_thumbnails(0..self.games.len())
+    }
+
+    /// replaces the game at `i` by `game`, keeping its thumbnail unless the box art changed
+    pub fn replace_game(&mut self, i: usize, mut game: Game) -> Command<Message> {
+        if game.box_art == self.games[i].box_art {
+            game.image = self.games[i].image.clone();
+            self.games[i] = game;
+            Command::none()
+        } else {
+            self.games[i] = game;
+            self.load_thumbnails([i])
+        }
+    }
+
+    /// loads the thumbnails of the games at `indices` in the background. Nothing is loaded until
+    /// the scale factor of the window is known.
+    pub fn load_thumbnails(&self, indices: impl IntoIterator<Item = usize>) -> Command<Message> {
+        let width = self.thumbnail_width;
+        if width == 0 {
+            return Command::none();
+        }
+        let jobs = indices
+            .into_iter()
+            .filter_map(|i| {
+                let g = &self.games[i];
+                g.box_art.clone().map(|b| (g.id(), b))
+            })
+            .collect::<Vec<_>>();
+        thumbnails::load_all(jobs, width)
+    }
+
+    /// lists the available verbs with the winetricks set for the game being edited
+    pub fn load_winetricks_verbs(&mut self) -> Command<Message> {
+        let program = self
+            .temp_settings
+            .as_ref()
+            .and_then(|a| a.0.get("wine:path_to_winetricks"))
+            .or(self
+                .default_config
+                .get("wine:path_to_winetricks")
+                .map(|a| &a.1))
+            .map(CValue::as_string)
+            .unwrap_or("winetricks".to_owned());
+        self.winetricks_verbs = None;
+        Command::perform(
+            winetricks::list_all(program),
+            Message::WinetricksVerbsLoaded,
+        )
+    }
+
+    /// looks for the wine builds again, in the background
+    pub fn load_wine_builds(&self) -> Command<Message> {
+        let cfg = self.default_config.clone();
+        Command::perform(
+            async { tokio::task::spawn_blocking(move || wine_builds::scan(&cfg)).await },
+            |builds| Message::WineBuildsLoaded(builds.unwrap_or_default()),
+        )
+    }
 
-                out
+    pub fn handle_input(&mut self, action: input::Action) -> Command<Message> {
+        match action {
+            input::Action::ToggleCouchMode => self.update(Message::ToggleCouchMode),
+            input::Action::Back if self.confirm_delete || self.confirm_subcommand.is_some() => {
+                self.update(Message::CancelDelete)
+            }
+            input::Action::Back if self.steam_grid_db => self.update(Message::CancelSGDB),
+            input::Action::Back => {
+                self.grid_status = GridStatus::GamesGrid;
+                Command::none()
+            }
+            _ if self.grid_status != GridStatus::GamesGrid
+                || self.confirm_delete
+                || self.confirm_subcommand.is_some()
+                || self.steam_grid_db =>
+            {
+                Command::none()
+            }
+            input::Action::Launch => match self.selected {
+                Some(i) if !self.games[i].is_running => self.update(Message::RunSelected),
+                _ => Command::none(),
+            },
+            input::Action::Kill => match self.selected {
+                Some(i) if self.games[i].is_running => self.update(Message::KillSelected),
+                _ => Command::none(),
             },
+            input::Action::Up
+            | input::Action::Down
+            | input::Action::Left
+            | input::Action::Right => {
+                let visible = filter::visible(&self.games, &self.search, &self.filter);
+                let columns = self.grid_columns();
+                let Some(i) = input::navigate(&visible, self.selected, columns, action) else {
+                    return Command::none();
+                };
+                let selected = self.update(Message::GameSelected(i));
+                let pos = visible.iter().position(|a| *a == i).unwrap_or(0);
+                Command::batch([selected, self.scroll_to_row(pos / columns)])
+            }
+        }
+    }
+
+    /// how many games are shown per row
+    fn grid_columns(&self) -> usize {
+        input::columns(
+            self.couch_mode,
+            self.library_view,
+            self.grid_viewport.map(|v| v.bounds().width),
+            ui::cell_size(self).width,
+        )
+    }
+
+    /// scrolls the games grid or list just enough for `row` to be visible
+    fn scroll_to_row(&self, row: usize) -> Command<Message> {
+        let Some(viewport) = self.grid_viewport else {
+            return Command::none();
+        };
+        let (height, top) = if !self.couch_mode && self.library_view == views::LibraryView::List {
+            // below the header
+            (ui::LIST_ROW_HEIGHT, (row + 1) as f32 * ui::LIST_ROW_HEIGHT)
+        } else {
+            let h = ui::cell_size(self).height;
+            (h, row as f32 * h)
+        };
+        let offset = viewport.absolute_offset().y;
+        let visible = viewport.bounds().height;
+        let y = if top < offset {
+            top
+        } else if top + height > offset + visible {
+            top + height - visible
+        } else {
+            return Command::none();
+        };
+        iced::widget::scrollable::scroll_to(
+            ui::GRID_ID.clone(),
+            iced::widget::scrollable::AbsoluteOffset { x: 0., y },
         )
     }
+
+    fn theme(&self) -> iced::Theme {
+        self.colors()
+            .iced_theme(format!("game_handler {}", self.theme_type))
+    }
+}
+
+/// the scale factor of the main window, or 1 if there is none
+fn get_scale_factor() -> Command<Message> {
+    iced::window::get_latest()
+        .then(|id| match id {
+            Some(id) => iced::window::get_scale_factor(id),
+            None => Command::done(1.),
+        })
+        .map(Message::ScaleFactorChanged)
+}
+
+/// a path for the toml of a new game that doesn't exist yet
+fn new_game_path(name: &str) -> std::path::PathBuf {
+    let name = make_path_proof(name.to_owned());
+    loop {
+        let random_id: u16 = rand::random();
+        let path = DIRS
+            .config_dir()
+            .join("games")
+            .join(random_id.to_string() + &name[..] + ".toml");
+        if !path.exists() {
+            return path;
+        }
+    }
 }
 
 fn make_path_proof(s: String) -> String {
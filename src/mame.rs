@@ -10,8 +10,8 @@ pub struct MameRunner {
     pub fullscreen: bool,
 }
 impl Runner for MameRunner {
-    fn get_command(&self) -> Command {
-        Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
+        Ok(Command {
             program: self.path_to_mame.clone(),
             args: vec![
                 if self.fullscreen { "-now" } else { "-w" }.to_owned(),
@@ -21,20 +21,20 @@ impl Runner for MameRunner {
             ],
             envs: std::collections::HashMap::new(),
             cwd: Some(crate::DIRS.data_dir().join("mame")),
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["MAME".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "MAME" => Some(Command {
+            "MAME" => Ok(Command {
                 program: self.path_to_mame.clone(),
                 args: vec![if self.fullscreen { "-now" } else { "-w" }.to_owned()],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
@@ -8,12 +8,12 @@ pub struct NativeRunner {
 }
 
 impl Runner for NativeRunner {
-    fn get_command(&self) -> Command {
-        Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
+        Ok(Command {
             program: self.path.clone(),
             args: self.args.clone(),
             envs: std::collections::HashMap::new(),
             cwd: None,
-        }
+        })
     }
 }
@@ -9,7 +9,7 @@ pub struct Pcsx2Runner {
 }
 
 impl Runner for Pcsx2Runner {
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
         let mut args = vec![];
         if self.fullscreen {
             args.push("-fullscreen".to_owned())
@@ -17,25 +17,25 @@ impl Runner for Pcsx2Runner {
         args.push("--".to_owned());
         args.push(self.path.clone());
 
-        Command {
+        Ok(Command {
             program: self.path_to_pcsx2.clone(),
             args,
             envs: std::collections::HashMap::new(),
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["pcsx2".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "pcsx2" => Some(Command {
+            "pcsx2" => Ok(Command {
                 program: self.path_to_pcsx2.clone(),
                 args: vec![],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
@@ -0,0 +1,335 @@
+//! Playtime history, stored as a log of every session played (`sessions.toml` in the data
+//! directory). Every value shown to the user is computed from it.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+const SESSIONS_FILE: &str = "sessions.toml";
+/// the flat databases used before the session log, migrated on first load
+const LEGACY_FILE: &str = "times.toml";
+const LEGACY_TY_FILE: &str = "times_ty.toml";
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// the id of the game, see `Game::id`
+    pub id: String,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub runner: String,
+    /// `None` when the game was killed by a signal or its status couldn't be read
+    pub exit_status: Option<i32>,
+    /// sessions migrated from `times.toml` only hold a total, and don't count towards recent
+    /// playtime
+    pub legacy: bool,
+}
+
+impl Session {
+    pub fn duration(&self) -> Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+
+    /// the part of the session that happened after `since`
+    fn duration_since(&self, since: SystemTime) -> Duration {
+        if self.legacy {
+            return Duration::ZERO;
+        }
+        self.end
+            .duration_since(self.start.max(since))
+            .unwrap_or_default()
+    }
+
+    fn to_toml(&self) -> toml::Table {
+        let secs = |a: SystemTime| {
+            a.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64
+        };
+        let mut out = toml::Table::new();
+        out.insert("id".to_owned(), toml::Value::String(self.id.clone()));
+        out.insert("start".to_owned(), toml::Value::Integer(secs(self.start)));
+        out.insert("end".to_owned(), toml::Value::Integer(secs(self.end)));
+        out.insert(
+            "runner".to_owned(),
+            toml::Value::String(self.runner.clone()),
+        );
+        if let Some(s) = self.exit_status {
+            out.insert("exit_status".to_owned(), toml::Value::Integer(s as i64));
+        }
+        if self.legacy {
+            out.insert("legacy".to_owned(), toml::Value::Boolean(true));
+        }
+        out
+    }
+
+    fn from_toml(table: &toml::Table) -> Option<Self> {
+        // out of range values make the session invalid rather than overflow
+        let time = |key: &str| {
+            let secs = u64::try_from(table.get(key)?.as_integer()?).ok()?;
+            SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+        };
+        Some(Self {
+            id: table.get("id")?.as_str()?.to_owned(),
+            start: time("start")?,
+            end: time("end")?,
+            runner: table
+                .get("runner")
+                .and_then(|a| a.as_str())
+                .unwrap_or_default()
+                .to_owned(),
+            exit_status: table
+                .get("exit_status")
+                .and_then(|a| a.as_integer())
+                .map(|a| a as i32),
+            legacy: table
+                .get("legacy")
+                .and_then(|a| a.as_bool())
+                .unwrap_or(false),
+        })
+    }
+}
+
+/// the playtime values of a single game
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GameStats {
+    pub total: Duration,
+    pub this_year: Duration,
+    pub last_30_days: Duration,
+    pub last_played: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PlaytimeDb {
+    pub sessions: Vec<Session>,
+    /// set when the history couldn't be fully read nor backed up, so that it isn't overwritten
+    read_only: bool,
+}
+
+impl PlaytimeDb {
+    /// reads the session log from the data directory, migrating the legacy databases if there is
+    /// no log yet. Errors are logged and result in an empty history, the unreadable file being
+    /// backed up first since it is replaced on the next save.
+    pub fn load() -> Self {
+        let path = crate::DIRS.data_dir().join(SESSIONS_FILE);
+        if !path.exists() {
+            return match Self::migrate() {
+                Ok(out) => {
+                    out.save();
+                    out
+                }
+                Err(e) => {
+                    // writing a history would keep the legacy playtime from ever being migrated
+                    log::error!(
+                        "couldn't migrate the legacy playtime database, playtime won't be \
+                         saved until it is fixed : {e}"
+                    );
+                    Self {
+                        sessions: vec![],
+                        read_only: true,
+                    }
+                }
+            };
+        }
+
+        let (sessions, broken) = read_sessions(&path);
+        let mut read_only = false;
+        if broken {
+            let secs = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let backup = path.with_extension(format!("toml.{secs}.bak"));
+            match std::fs::copy(&path, &backup) {
+                Ok(_) => log::warn!("the playtime history was backed up to {backup:?}"),
+                Err(e) => {
+                    log::error!(
+                        "couldn't back up the playtime history to {backup:?}, it won't be \
+                         saved : {e}"
+                    );
+                    read_only = true;
+                }
+            }
+        }
+        Self {
+            sessions,
+            read_only,
+        }
+    }
+
+    /// turns the total of every game in `times.toml` into a single legacy session ending now.
+    /// Nothing is migrated nor moved when a legacy database can't be read.
+    fn migrate() -> Result<Self, String> {
+        let legacy = read_legacy_db(&crate::DIRS.data_dir().join(LEGACY_FILE))?;
+        let this_year = read_legacy_db(&crate::DIRS.data_dir().join(LEGACY_TY_FILE))?;
+        if legacy.is_empty() {
+            return Ok(Self::default());
+        }
+        if this_year.values().any(|a| !a.is_zero()) {
+            // there is no telling when that playtime happened
+            log::warn!(
+                "the playtime of this year from {LEGACY_TY_FILE} isn't migrated, it only counts \
+                 towards the total playtime"
+            );
+        }
+        log::info!(
+            "migrating playtime of {} games to the session log",
+            legacy.len()
+        );
+        let now = SystemTime::now();
+        let mut sessions = legacy
+            .into_iter()
+            .filter(|(_, d)| !d.is_zero())
+            .filter_map(|(id, d)| {
+                let start = now.checked_sub(d);
+                if start.is_none() {
+                    log::error!("ignoring invalid playtime {d:?} of {id}");
+                }
+                Some(Session {
+                    id,
+                    start: start?,
+                    end: now,
+                    runner: String::new(),
+                    exit_status: None,
+                    legacy: true,
+                })
+            })
+            .collect::<Vec<_>>();
+        sessions.sort_by(|a, b| a.id.cmp(&b.id));
+        for f in [LEGACY_FILE, LEGACY_TY_FILE] {
+            let path = crate::DIRS.data_dir().join(f);
+            if let Err(e) = std::fs::rename(&path, path.with_extension("toml.bak")) {
+                log::warn!("couldn't back up {path:?} : {e}");
+            }
+        }
+        Ok(Self {
+            sessions,
+            read_only: false,
+        })
+    }
+
+    /// writes the whole history back to the data directory. The file is replaced at once, so
+    /// that a crash doesn't leave it truncated.
+    pub fn save(&self) {
+        if self.read_only {
+            log::error!("not saving the playtime history, as it couldn't be read");
+            return;
+        }
+        let mut out = toml::Table::new();
+        out.insert(
+            "session".to_owned(),
+            toml::Value::Array(
+                self.sessions
+                    .iter()
+                    .map(|a| toml::Value::Table(a.to_toml()))
+                    .collect(),
+            ),
+        );
+        let path = crate::DIRS.data_dir().join(SESSIONS_FILE);
+        let temp = path.with_extension("toml.tmp");
+        if let Err(e) = std::fs::write(&temp, toml::to_string(&out).unwrap_or_default())
+            .and_then(|_| std::fs::rename(&temp, &path))
+        {
+            log::error!("couldn't write playtime history : {e}");
+        }
+    }
+
+    /// changes the history and saves it. The history is read again first, since the command
+    /// line and the launcher may both be recording sessions.
+    fn update(&mut self, f: impl FnOnce(&mut Vec<Session>)) {
+        if !self.read_only {
+            let path = crate::DIRS.data_dir().join(SESSIONS_FILE);
+            match read_sessions(&path) {
+                (sessions, false) => self.sessions = sessions,
+                (_, true) => log::warn!("couldn't read the playtime history again, replacing it"),
+            }
+        }
+        f(&mut self.sessions);
+        self.save();
+    }
+
+    /// adds a session to the history and saves it
+    pub fn record(&mut self, session: Session) {
+        log::info!("game {} played for {:?}", session.id, session.duration());
+        self.update(|sessions| sessions.push(session));
+    }
+
+    /// changes the id of the sessions of the games in `ids` (old id => new id), and saves the
+    /// history
+    pub fn rename_ids(&mut self, ids: &HashMap<String, String>) {
+        self.update(|sessions| {
+            for s in sessions.iter_mut() {
+                if let Some(new) = ids.get(&s.id) {
+                    s.id = new.clone();
+                }
+            }
+        });
+    }
+
+    pub fn stats(&self, id: &str) -> GameStats {
+        let year_start = year_start();
+        let month_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 3600);
+        let mut out = GameStats::default();
+        for s in self.sessions.iter().filter(|a| a.id == id) {
+            out.total += s.duration();
+            out.this_year += s.duration_since(year_start);
+            out.last_30_days += s.duration_since(month_ago);
+            if !s.legacy {
+                out.last_played = out.last_played.max(Some(s.end));
+            }
+        }
+        out
+    }
+}
+
+/// the first instant of the current year, in local time
+fn year_start() -> SystemTime {
+    use chrono::{Datelike, TimeZone};
+    chrono::Local
+        .with_ymd_and_hms(chrono::Local::now().year(), 1, 1, 0, 0, 0)
+        .earliest()
+        .map(SystemTime::from)
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// reads the session log at `path`, along with whether some of it couldn't be read
+fn read_sessions(path: &std::path::Path) -> (Vec<Session>, bool) {
+    let mut broken = false;
+    let table = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|a| a.parse::<toml::Table>().map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            log::error!("couldn't read playtime history : {e}");
+            broken = true;
+            toml::Table::new()
+        });
+    let sessions = match table.get("session") {
+        Some(toml::Value::Array(a)) => a
+            .iter()
+            .filter_map(|s| {
+                let out = s.as_table().and_then(Session::from_toml);
+                if out.is_none() {
+                    log::error!("ignoring invalid session in playtime history : {s}");
+                    broken = true;
+                }
+                out
+            })
+            .collect(),
+        _ => vec![],
+    };
+    (sessions, broken)
+}
+
+/// reads a legacy playtime database (a toml table of seconds keyed by game id), which is empty
+/// when there is none
+fn read_legacy_db(path: &std::path::Path) -> Result<HashMap<String, Duration>, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(a) => a,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(format!("{path:?} : {e}")),
+    };
+    Ok(content
+        .parse::<toml::Table>()
+        .map_err(|e| format!("{path:?} : {e}"))?
+        .into_iter()
+        .filter_map(|(k, v)| Some((k, Duration::from_secs(v.as_integer()?.try_into().ok()?))))
+        .collect())
+}
@@ -5,12 +5,22 @@ use tokio::io::AsyncBufReadExt;
 use tokio_stream::wrappers::SplitStream;
 use tokio_stream::StreamExt;
 
+/// events are tagged with the id of the game, as its index can change while it runs
 pub enum Event {
-    Ready(usize, mpsc::Sender<PSubInput>),
-    GotLogs(usize, String),
-    ProcessEnded(usize),
+    Ready(String, mpsc::Sender<PSubInput>),
+    GotLogs(String, String),
+    /// the game exited, with the exit status of the command that launched it if it has one, and
+    /// when it ended
+    ProcessEnded(String, Option<i32>, std::time::SystemTime),
+    LaunchFailed(String, crate::games::LaunchError),
+    /// the processes spawned by the game changed
+    Processes(String, Vec<(sysinfo::Pid, String)>),
 }
 
+/// how often the processes of the game are looked for, which reads all of `/proc`. Logs are read
+/// much more often while they are shown.
+const TREE_REFRESH: std::time::Duration = std::time::Duration::from_millis(500);
+
 enum PSubState {
     Starting,
     Ready(mpsc::Receiver<PSubInput>),
@@ -22,12 +32,40 @@ pub enum PSubInput {
     ReadInput,
 }
 
-pub fn get_psub(idx: usize, cmd_builder: Option<crate::games::Command>) -> Subscription<Event> {
+/// `grace` is how long processes are given to exit after SIGTERM before they get SIGKILLed.
+/// With a `rule`, the game keeps running after the command exits as long as matching processes do.
+pub fn get_psub(
+    id: String,
+    cmd_builder: Option<crate::games::Command>,
+    grace: std::time::Duration,
+    rule: Option<crate::process_tree::TrackingRule>,
+) -> Subscription<Event> {
     iced::Subscription::run_with_id(
-        idx,
+        id.clone(),
         iced::stream::channel(100, move |mut output| async move {
             let mut state = PSubState::Starting;
-            let mut proc = cmd_builder.unwrap().run().unwrap();
+            let proc = cmd_builder
+                .ok_or(crate::games::LaunchError::Spawn(
+                    String::new(),
+                    "no command to run".to_owned(),
+                ))
+                .and_then(|a| a.run());
+            let mut proc = match proc {
+                Ok(p) => p,
+                Err(e) => {
+                    if let Err(e) = output.send(Event::LaunchFailed(id.clone(), e)).await {
+                        log::error!("Unable to send data from psub : {e}");
+                    }
+                    // the subscription is dropped as soon as the game isn't running anymore
+                    iced::futures::future::pending::<()>().await;
+                    unreachable!()
+                }
+            };
+            let mut tree =
+                crate::process_tree::ProcessTree::new(proc.id().unwrap_or_default(), rule);
+            let mut last_tree_refresh = std::time::Instant::now();
+            // when the command exited, the game possibly running on
+            let mut launcher_exit = None;
             let stdout = proc.stdout.take().unwrap();
             let stderr = proc.stderr.take().unwrap();
 
@@ -48,7 +86,7 @@ pub fn get_psub(idx: usize, cmd_builder: Option<crate::games::Command>) -> Subsc
                 match &mut state {
                     PSubState::Starting => {
                         let (sender, receiver) = mpsc::channel(100);
-                        if let Err(e) = output.send(Event::Ready(idx, sender)).await {
+                        if let Err(e) = output.send(Event::Ready(id.clone(), sender)).await {
                             log::error!(
                                 "Couldn't send back the sender : {e}. This will most likely panic"
                             );
@@ -79,7 +117,7 @@ pub fn get_psub(idx: usize, cmd_builder: Option<crate::games::Command>) -> Subsc
                                             }
                                             if let Err(e) = output
                                                 .send(Event::GotLogs(
-                                                    idx,
+                                                    id.clone(),
                                                     String::from_utf8_lossy(ct).to_string(),
                                                 ))
                                                 .await
@@ -94,10 +132,20 @@ pub fn get_psub(idx: usize, cmd_builder: Option<crate::games::Command>) -> Subsc
                                         std::task::Poll::Pending => break,
                                     }
                                 }
-                                if let Err(e) = proc.kill().await {
-                                    log::error!("Unable to kill process : {e}");
+                                tree.terminate(grace).await;
+                                if let Ok(None) = proc.try_wait() {
+                                    if let Err(e) = proc.kill().await {
+                                        log::error!("Unable to kill process : {e}");
+                                    }
                                 }
-                                if let Err(e) = output.send(Event::ProcessEnded(idx)).await {
+                                let status = proc.try_wait().ok().flatten().and_then(|a| a.code());
+                                let end = tree.game_end(
+                                    launcher_exit.unwrap_or_else(std::time::SystemTime::now),
+                                );
+                                if let Err(e) = output
+                                    .send(Event::ProcessEnded(id.clone(), status, end))
+                                    .await
+                                {
                                     log::error!("Unable to send data from psub : {e}");
                                 }
                             }
@@ -128,15 +176,40 @@ pub fn get_psub(idx: usize, cmd_builder: Option<crate::games::Command>) -> Subsc
                                     }
                                 }
                                 if !logs.is_empty() {
-                                    if let Err(e) = output.send(Event::GotLogs(idx, logs)).await {
+                                    if let Err(e) =
+                                        output.send(Event::GotLogs(id.clone(), logs)).await
+                                    {
                                         log::error!("Unable to send data from psub : {e}");
                                     }
                                 }
-                                if let Ok(Some(_exit_status)) = proc.try_wait() {
-                                    if let Err(e) = output.send(Event::ProcessEnded(idx)).await {
+                                if last_tree_refresh.elapsed() < TREE_REFRESH {
+                                    continue;
+                                }
+                                last_tree_refresh = std::time::Instant::now();
+                                if tree.refresh() {
+                                    if let Err(e) = output
+                                        .send(Event::Processes(id.clone(), tree.alive()))
+                                        .await
+                                    {
                                         log::error!("Unable to send data from psub : {e}");
                                     }
                                 }
+                                if let Ok(Some(exit_status)) = proc.try_wait() {
+                                    let exited = *launcher_exit
+                                        .get_or_insert_with(std::time::SystemTime::now);
+                                    if tree.game_exited() {
+                                        if let Err(e) = output
+                                            .send(Event::ProcessEnded(
+                                                id.clone(),
+                                                exit_status.code(),
+                                                tree.game_end(exited),
+                                            ))
+                                            .await
+                                        {
+                                            log::error!("Unable to send data from psub : {e}");
+                                        }
+                                    }
+                                }
                             }
                         }
                     }
@@ -0,0 +1,268 @@
+//! Tracking of every process spawned by a game, such that killing it doesn't only kill a wrapper
+//! (wine, umu, gamescope...) and leave the actual game running. Process groups are read from
+//! procfs, so on systems without it only the descendants of the launcher are tracked.
+
+use std::collections::HashSet;
+
+use sysinfo::{Pid, PidExt, ProcessExt, ProcessRefreshKind, ProcessStatus, Signal, SystemExt};
+
+/// how long a launcher that already exited is waited for to spawn a process matching the
+/// tracking rule, before the game is considered over
+const TRACKING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// processes that should keep a game running after the command that launched it exited, for
+/// launchers that hand the game over to another process (steam, scripts that fork...)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackingRule {
+    /// processes whose executable has this file name
+    Name(String),
+    /// processes whose executable, first argument or working directory starts with this path
+    PathPrefix(String),
+    /// the steam `reaper` process of this AppID, which lives as long as the game
+    SteamAppId(String),
+}
+
+impl TrackingRule {
+    /// builds a rule from the `tracked_process` setting of a game. An empty AppID falls back to
+    /// `path_to_game`, which is the AppID for steam games.
+    pub fn from_config(kind: &str, value: &str, path_to_game: &str) -> Option<Self> {
+        let value = value.trim();
+        match kind {
+            "executable name" if !value.is_empty() => Some(Self::Name(value.to_owned())),
+            "path prefix" if !value.is_empty() => Some(Self::PathPrefix(value.to_owned())),
+            "steam appid" => {
+                let id = if value.is_empty() {
+                    path_to_game.trim()
+                } else {
+                    value
+                };
+                if id.is_empty() {
+                    None
+                } else {
+                    Some(Self::SteamAppId(id.to_owned()))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn matches(&self, p: &sysinfo::Process) -> bool {
+        let file_name = |a: &std::path::Path| {
+            a.file_name()
+                .map(|a| a.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        match self {
+            // names read from procfs are truncated, the executable and first argument aren't
+            Self::Name(n) => {
+                p.name() == n
+                    || file_name(p.exe()) == *n
+                    || p.cmd()
+                        .first()
+                        .map(|a| file_name(std::path::Path::new(a)) == *n)
+                        .unwrap_or(false)
+            }
+            Self::PathPrefix(prefix) => {
+                p.exe().starts_with(prefix)
+                    || p.cwd().starts_with(prefix)
+                    || p.cmd()
+                        .first()
+                        .map(|a| a.starts_with(&prefix[..]))
+                        .unwrap_or(false)
+            }
+            Self::SteamAppId(id) => {
+                let arg = format!("AppId={id}");
+                p.cmd().contains(&arg)
+            }
+        }
+    }
+}
+
+pub struct ProcessTree {
+    root: Pid,
+    tracked: HashSet<Pid>,
+    rule: Option<TrackingRule>,
+    /// processes matching `rule`, and their descendants
+    matched: HashSet<Pid>,
+    /// whether a process ever matched `rule`
+    seen_match: bool,
+    started: std::time::Instant,
+    system: sysinfo::System,
+}
+
+impl ProcessTree {
+    /// `root` should have been spawned in its own process group (see `Command::run`)
+    pub fn new(root: u32, rule: Option<TrackingRule>) -> Self {
+        let root = Pid::from_u32(root);
+        let mut out = Self {
+            root,
+            tracked: HashSet::from([root]),
+            rule,
+            matched: HashSet::new(),
+            seen_match: false,
+            started: std::time::Instant::now(),
+            system: sysinfo::System::new(),
+        };
+        out.refresh();
+        out
+    }
+
+    /// updates the tracked processes : dead ones are forgotten, and both descendants and members
+    /// of the process group of the root are added, as well as processes matching the tracking
+    /// rule and their descendants. Processes that were already tracked stay so even if their
+    /// parent dies. Returns whether the set changed.
+    pub fn refresh(&mut self) -> bool {
+        self.system
+            .refresh_processes_specifics(ProcessRefreshKind::new());
+        // zombies are dead, they are only waiting for their parent (often us, for the root) to
+        // reap them
+        let processes = self
+            .system
+            .processes()
+            .iter()
+            .filter(|(_, p)| p.status() != ProcessStatus::Zombie)
+            .map(|(pid, p)| (*pid, p))
+            .collect::<std::collections::HashMap<_, _>>();
+
+        let before = self.tracked.len();
+        self.tracked.retain(|p| processes.contains_key(p));
+        let mut changed = before != self.tracked.len();
+
+        loop {
+            let new = processes
+                .iter()
+                .filter(|(pid, p)| {
+                    !self.tracked.contains(pid)
+                        && (p
+                            .parent()
+                            .map(|a| self.tracked.contains(&a))
+                            .unwrap_or(false)
+                            || process_group(**pid) == Some(self.root.as_u32()))
+                })
+                .map(|(pid, _)| *pid)
+                .collect::<Vec<_>>();
+            if new.is_empty() {
+                break;
+            }
+            changed = true;
+            self.tracked.extend(new);
+        }
+
+        if let Some(rule) = &self.rule {
+            let before = self.matched.len();
+            self.matched.retain(|p| processes.contains_key(p));
+            changed |= before != self.matched.len();
+            loop {
+                let new = processes
+                    .iter()
+                    .filter(|(pid, p)| {
+                        !self.matched.contains(pid)
+                            && (p
+                                .parent()
+                                .map(|a| self.matched.contains(&a))
+                                .unwrap_or(false)
+                                || rule.matches(p))
+                    })
+                    .map(|(pid, _)| *pid)
+                    .collect::<Vec<_>>();
+                if new.is_empty() {
+                    break;
+                }
+                changed = true;
+                self.seen_match = true;
+                self.matched.extend(new);
+            }
+        }
+        changed
+    }
+
+    /// whether the game is over once its launcher exited : without a tracking rule it always is,
+    /// otherwise it lasts until every matching process is gone (or none showed up in time)
+    pub fn game_exited(&self) -> bool {
+        match self.rule {
+            None => true,
+            Some(_) if self.seen_match => self.matched.is_empty(),
+            Some(_) => self.started.elapsed() > TRACKING_TIMEOUT,
+        }
+    }
+
+    /// when the game ended, given when its launcher exited : now, unless the tracking rule never
+    /// matched, in which case the time spent waiting for a match isn't playtime
+    pub fn game_end(&self, launcher_exit: std::time::SystemTime) -> std::time::SystemTime {
+        if self.rule.is_some() && !self.seen_match {
+            launcher_exit
+        } else {
+            std::time::SystemTime::now()
+        }
+    }
+
+    /// the processes that make up the game. Once the launcher is gone with a tracking rule, only
+    /// the matching processes are, such that a steam client started by it is left alone.
+    fn game_processes(&self) -> HashSet<Pid> {
+        let mut out = self.matched.clone();
+        if self.rule.is_none() || self.tracked.contains(&self.root) {
+            out.extend(self.tracked.iter());
+        }
+        out
+    }
+
+    /// the tracked processes that are still alive, along with their names
+    pub fn alive(&self) -> Vec<(Pid, String)> {
+        let mut out = self
+            .game_processes()
+            .iter()
+            .filter_map(|p| self.system.process(*p).map(|a| (*p, a.name().to_owned())))
+            .collect::<Vec<_>>();
+        out.sort_by_key(|a| a.0);
+        out
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.game_processes().is_empty()
+    }
+
+    /// sends `signal` to every process of the game
+    pub fn signal(&self, signal: Signal) {
+        for pid in self.game_processes().iter() {
+            if let Some(p) = self.system.process(*pid) {
+                if p.kill_with(signal) != Some(true) {
+                    log::warn!("couldn't send {signal:?} to process {pid} ({})", p.name());
+                }
+            }
+        }
+    }
+
+    /// sends SIGTERM to the whole tree, then SIGKILL to whatever is still alive after `grace`
+    pub async fn terminate(&mut self, grace: std::time::Duration) {
+        self.refresh();
+        log::info!("terminating processes {:?}", self.alive());
+        self.signal(Signal::Term);
+
+        let start = std::time::Instant::now();
+        while start.elapsed() < grace {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            self.refresh();
+            if self.is_empty() {
+                return;
+            }
+        }
+
+        log::warn!(
+            "processes {:?} survived SIGTERM, sending SIGKILL",
+            self.alive()
+        );
+        self.signal(Signal::Kill);
+    }
+}
+
+/// the process group of `pid`, read from procfs
+fn process_group(pid: Pid) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // the second field is the name between parentheses, which may contain spaces
+    stat.rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .nth(2)?
+        .parse()
+        .ok()
+}
@@ -8,29 +8,29 @@ pub struct Rpcs3Runner {
 }
 
 impl Runner for Rpcs3Runner {
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
         let mut envs = std::collections::HashMap::new();
         envs.insert("QT_QPA_PLATFORM".to_owned(), "xcb".to_owned());
 
-        Command {
+        Ok(Command {
             program: self.path_to_rpcs3.clone(),
             args: vec!["--no-gui".to_owned(), self.path.clone()],
             envs,
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["rpcs3".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "rpcs3" => Some(Command {
+            "rpcs3" => Ok(Command {
                 program: self.path_to_rpcs3.clone(),
                 args: vec![],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
@@ -8,26 +8,26 @@ pub struct RyujinxRunner {
 }
 
 impl Runner for RyujinxRunner {
-    fn get_command(&self) -> Command {
-        Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
+        Ok(Command {
             program: self.path_to_ryujinx.clone(),
             args: vec![self.path.clone()],
             envs: std::collections::HashMap::new(),
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["ryujinx".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "ryujinx" => Some(Command {
+            "ryujinx" => Ok(Command {
                 program: self.path_to_ryujinx.clone(),
                 args: vec![],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
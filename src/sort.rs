@@ -1,13 +1,30 @@
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
+use crate::config::CValue;
 use crate::games::Game;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Sorts {
+    #[default]
     Name,
     ReleaseDate,
+    Playtime,
+    LastPlayed,
+    DateAdded,
+    Runner,
 }
 
 impl Sorts {
+    pub const ALL: [Sorts; 6] = [
+        Sorts::Name,
+        Sorts::ReleaseDate,
+        Sorts::Playtime,
+        Sorts::LastPlayed,
+        Sorts::DateAdded,
+        Sorts::Runner,
+    ];
+
     pub fn get_fn(&self) -> fn(&Game, &Game) -> Ordering {
         match self {
             Self::Name => |g: &Game, o: &Game| g.name.to_uppercase().cmp(&o.name.to_uppercase()),
@@ -16,25 +33,61 @@ impl Sorts {
                     .unwrap_or(isize::MAX)
                     .cmp(&o.release_year.unwrap_or(isize::MAX))
             },
+            Self::Playtime => |g: &Game, o: &Game| g.playtime.total.cmp(&o.playtime.total),
+            Self::LastPlayed => {
+                |g: &Game, o: &Game| g.playtime.last_played.cmp(&o.playtime.last_played)
+            }
+            Self::DateAdded => |g: &Game, o: &Game| g.date_added.cmp(&o.date_added),
+            Self::Runner => |g: &Game, o: &Game| g.runner_id.cmp(&o.runner_id),
         }
     }
+
+    /// the sort saved in the global settings, and whether it is descending
+    pub fn from_config(cfg: &HashMap<String, (String, CValue)>) -> (Self, bool) {
+        let sort = cfg
+            .get("launcher:sort")
+            .map(|a| a.1.as_string())
+            .and_then(|s| Self::ALL.into_iter().find(|a| a.to_string() == s))
+            .unwrap_or_default();
+        let descending = matches!(
+            cfg.get("launcher:sort_descending"),
+            Some((_, CValue::Bool(true)))
+        );
+        (sort, descending)
+    }
+
+    /// the value of the `launcher:sort` setting
+    pub fn to_config(self) -> CValue {
+        CValue::OneOff(
+            Self::ALL.iter().map(|a| a.to_string()).collect(),
+            Self::ALL.iter().position(|a| *a == self).unwrap_or(0),
+        )
+    }
 }
 
-impl crate::MainGUI {
-    /// sorts the games according to `key` while moving `selected` accordingly if applicable.
-    /// doesn't work at all
-    pub fn sort(&mut self, key: fn(&Game, &Game) -> Ordering) {
-        if let Some(i) = self.selected {
-            let mut indices = (0..self.games.len()).collect::<Vec<_>>();
-            indices.sort_unstable_by(|i, j| key(&self.games[*i], &self.games[*j]));
-            self.games.sort_unstable_by(key);
-            self.selected = Some(indices[i]);
-        } else {
-            self.games.sort_unstable_by(key);
+impl std::fmt::Display for Sorts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name => write!(f, "name"),
+            Self::ReleaseDate => write!(f, "release date"),
+            Self::Playtime => write!(f, "playtime"),
+            Self::LastPlayed => write!(f, "last played"),
+            Self::DateAdded => write!(f, "date added"),
+            Self::Runner => write!(f, "runner"),
         }
     }
 }
 
-pub fn sort_none_selected(games: &mut Vec<Game>, key: fn(&Game, &Game) -> Ordering) {
-    games.sort_unstable_by(key);
+/// sorts `games` by `sort`, games that compare equal being sorted by name
+pub fn sort_games(games: &mut [Game], sort: Sorts, descending: bool) {
+    let key = sort.get_fn();
+    let name = Sorts::Name.get_fn();
+    games.sort_by(|a, b| {
+        let out = key(a, b).then_with(|| name(a, b));
+        if descending {
+            out.reverse()
+        } else {
+            out
+        }
+    });
 }
@@ -0,0 +1,406 @@
+//! Play statistics computed from the playtime history, and the bar charts used to show them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate};
+use iced::widget::canvas::{self, event, Event};
+use iced::{mouse, Point, Rectangle, Size, Theme};
+
+use crate::games::Game;
+use crate::playtime::PlaytimeDb;
+use crate::Message;
+
+/// how many games are shown in the top games chart
+const TOP_GAMES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    pub const ALL: [Period; 3] = [Period::Day, Period::Week, Period::Month];
+
+    /// how many periods the timeline shows
+    fn count(&self) -> usize {
+        match self {
+            Period::Day => 30,
+            Period::Week => 12,
+            Period::Month => 12,
+        }
+    }
+
+    /// the first day of the period containing `date`
+    fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => {
+                date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// the first day of the period before the one starting at `start`
+    fn previous(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => start - chrono::Duration::days(1),
+            Period::Week => start - chrono::Duration::weeks(1),
+            Period::Month => start
+                .checked_sub_months(chrono::Months::new(1))
+                .unwrap_or(start),
+        }
+    }
+
+    fn label(&self, start: NaiveDate) -> String {
+        match self {
+            Period::Day | Period::Week => start.format("%d/%m").to_string(),
+            Period::Month => start.format("%b %y").to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Period::Day => write!(f, "day"),
+            Period::Week => write!(f, "week"),
+            Period::Month => write!(f, "month"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bar {
+    pub label: String,
+    pub value: Duration,
+    /// the index of the game the bar stands for, if any. Clicking the bar selects it.
+    pub game: Option<usize>,
+}
+
+/// the values read from the whole session history, computed again when it changes as that takes
+/// a while
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub total: Duration,
+    /// playtime per period, oldest first
+    pub timeline: Vec<Bar>,
+    pub runners: Vec<Bar>,
+    /// consecutive days played up to today (or yesterday, if nothing was played yet today)
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+pub fn compute(db: &PlaytimeDb, games: &[Game], period: Period) -> Stats {
+    let daily = daily_playtime(db);
+
+    let mut timeline = vec![];
+    let mut start = period.start_of(chrono::Local::now().date_naive());
+    for _ in 0..period.count() {
+        let end = match period {
+            Period::Day => start + chrono::Duration::days(1),
+            Period::Week => start + chrono::Duration::weeks(1),
+            Period::Month => start
+                .checked_add_months(chrono::Months::new(1))
+                .unwrap_or(start),
+        };
+        timeline.push(Bar {
+            label: period.label(start),
+            value: daily.range(start..end).map(|(_, d)| *d).sum(),
+            game: None,
+        });
+        start = period.previous(start);
+    }
+    timeline.reverse();
+
+    let runner_of = games
+        .iter()
+        .map(|g| (g.id(), g.runner_id.clone()))
+        .collect::<HashMap<_, _>>();
+    let mut runners = HashMap::<String, Duration>::new();
+    for s in db.sessions.iter() {
+        let runner = if s.runner.is_empty() {
+            runner_of
+                .get(&s.id)
+                .cloned()
+                .unwrap_or("unknown".to_owned())
+        } else {
+            s.runner.clone()
+        };
+        *runners.entry(runner).or_default() += s.duration();
+    }
+    let mut runners = runners
+        .into_iter()
+        .map(|(label, value)| Bar {
+            label,
+            value,
+            game: None,
+        })
+        .collect::<Vec<_>>();
+    runners.sort_by_key(|a| std::cmp::Reverse(a.value));
+
+    let (current_streak, longest_streak) = streaks(&daily);
+    Stats {
+        total: db.sessions.iter().map(|a| a.duration()).sum(),
+        timeline,
+        runners,
+        current_streak,
+        longest_streak,
+    }
+}
+
+/// the games played the most, which follow the games as they are sorted or removed
+pub fn top_games(games: &[Game]) -> Vec<Bar> {
+    // totals include legacy sessions, which can't be placed in time
+    let mut out = games
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| !g.playtime.total.is_zero())
+        .map(|(i, g)| Bar {
+            label: g.name.clone(),
+            value: g.playtime.total,
+            game: Some(i),
+        })
+        .collect::<Vec<_>>();
+    out.sort_by_key(|a| std::cmp::Reverse(a.value));
+    out.truncate(TOP_GAMES);
+    out
+}
+
+/// playtime per local day, with sessions spanning midnight split between days
+fn daily_playtime(db: &PlaytimeDb) -> BTreeMap<NaiveDate, Duration> {
+    let mut out = BTreeMap::new();
+    for s in db.sessions.iter().filter(|a| !a.legacy) {
+        let mut start: chrono::DateTime<chrono::Local> = s.start.into();
+        let end: chrono::DateTime<chrono::Local> = s.end.into();
+        while start < end {
+            let chunk_end = start
+                .date_naive()
+                .succ_opt()
+                .and_then(|a| a.and_hms_opt(0, 0, 0))
+                .and_then(|a| a.and_local_timezone(chrono::Local).earliest())
+                .filter(|a| *a > start)
+                .unwrap_or(end)
+                .min(end);
+            *out.entry(start.date_naive()).or_insert(Duration::ZERO) +=
+                (chunk_end - start).to_std().unwrap_or_default();
+            start = chunk_end;
+        }
+    }
+    out
+}
+
+/// the current and longest streaks of consecutive days played
+fn streaks(daily: &BTreeMap<NaiveDate, Duration>) -> (usize, usize) {
+    let played = |d: &NaiveDate| daily.get(d).map(|a| !a.is_zero()).unwrap_or(false);
+
+    let mut longest = 0;
+    let mut streak = 0;
+    let mut last: Option<NaiveDate> = None;
+    for d in daily.keys().filter(|a| played(a)) {
+        streak = match last {
+            Some(l) if l.succ_opt() == Some(*d) => streak + 1,
+            _ => 1,
+        };
+        longest = longest.max(streak);
+        last = Some(*d);
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let mut day = if played(&today) {
+        Some(today)
+    } else {
+        today.pred_opt()
+    };
+    let mut current = 0;
+    while let Some(d) = day.filter(played) {
+        current += 1;
+        day = d.pred_opt();
+    }
+    (current, longest)
+}
+
+pub fn short_duration(d: Duration) -> String {
+    let mins = d.as_secs() / 60;
+    if mins < 60 {
+        format!("{mins}m")
+    } else {
+        format!("{:.1}h", mins as f32 / 60.)
+    }
+}
+
+/// a bar chart drawn with a canvas, either as columns or as labelled rows
+pub struct BarChart {
+    pub bars: Vec<Bar>,
+    /// draws one row per bar with its label on the left instead of columns
+    pub horizontal: bool,
+    pub selected: Option<usize>,
+}
+
+const LABEL_HEIGHT: f32 = 20.;
+const TEXT_SIZE: f32 = 14.;
+
+impl BarChart {
+    fn max(&self) -> f32 {
+        self.bars
+            .iter()
+            .map(|a| a.value.as_secs_f32())
+            .fold(0., f32::max)
+            .max(1.)
+    }
+
+    fn label_width(&self, size: Size) -> f32 {
+        (size.width * 0.35).min(250.)
+    }
+
+    /// the area covered by the bar `i`
+    fn bar_bounds(&self, i: usize, size: Size) -> Rectangle {
+        let ratio = self.bars[i].value.as_secs_f32() / self.max();
+        if self.horizontal {
+            let row = size.height / self.bars.len() as f32;
+            let x = self.label_width(size);
+            Rectangle::new(
+                Point::new(x, row * i as f32 + row * 0.15),
+                Size::new((size.width - x - 60.).max(0.) * ratio, row * 0.7),
+            )
+        } else {
+            let column = size.width / self.bars.len() as f32;
+            let height = (size.height - 2. * LABEL_HEIGHT).max(0.) * ratio;
+            Rectangle::new(
+                Point::new(
+                    column * i as f32 + column * 0.1,
+                    size.height - LABEL_HEIGHT - height,
+                ),
+                Size::new(column * 0.8, height),
+            )
+        }
+    }
+
+    /// the bar whose row or column contains `p`
+    fn bar_at(&self, p: Point, size: Size) -> Option<usize> {
+        if self.bars.is_empty() {
+            return None;
+        }
+        let i = if self.horizontal {
+            p.y / (size.height / self.bars.len() as f32)
+        } else {
+            p.x / (size.width / self.bars.len() as f32)
+        } as usize;
+        (i < self.bars.len()).then_some(i)
+    }
+}
+
+impl canvas::Program<Message> for BarChart {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            if let Some(g) = cursor
+                .position_in(bounds)
+                .and_then(|p| self.bar_at(p, bounds.size()))
+                .and_then(|i| self.bars[i].game)
+            {
+                return (event::Status::Captured, Some(Message::GameSelected(g)));
+            }
+        }
+        (event::Status::Ignored, None)
+    }
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let pal = theme.extended_palette();
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let size = bounds.size();
+        let hovered = cursor
+            .position_in(bounds)
+            .and_then(|p| self.bar_at(p, size));
+        let text = |content: String, position: Point| canvas::Text {
+            content,
+            position,
+            color: pal.background.base.text,
+            size: TEXT_SIZE.into(),
+            vertical_alignment: iced::alignment::Vertical::Center,
+            ..Default::default()
+        };
+        // only some column labels are drawn when they wouldn't fit
+        let label_step = (self.bars.len() / 10).max(1);
+
+        for (i, bar) in self.bars.iter().enumerate() {
+            let rect = self.bar_bounds(i, size);
+            let highlighted =
+                hovered == Some(i) || (bar.game.is_some() && bar.game == self.selected);
+            frame.fill_rectangle(
+                rect.position(),
+                rect.size(),
+                if highlighted {
+                    pal.primary.strong.color
+                } else {
+                    pal.primary.base.color
+                },
+            );
+
+            if self.horizontal {
+                let y = rect.center_y();
+                frame.fill_text(text(bar.label.clone(), Point::new(0., y)));
+                frame.fill_text(text(
+                    short_duration(bar.value),
+                    Point::new(rect.x + rect.width + 5., y),
+                ));
+            } else {
+                let x = rect.center_x();
+                if i % label_step == 0 || hovered == Some(i) {
+                    frame.fill_text(canvas::Text {
+                        horizontal_alignment: iced::alignment::Horizontal::Center,
+                        ..text(
+                            bar.label.clone(),
+                            Point::new(x, size.height - LABEL_HEIGHT / 2.),
+                        )
+                    });
+                }
+                if hovered == Some(i) {
+                    frame.fill_text(canvas::Text {
+                        horizontal_alignment: iced::alignment::Horizontal::Center,
+                        ..text(
+                            short_duration(bar.value),
+                            Point::new(x, rect.y - LABEL_HEIGHT / 2.),
+                        )
+                    });
+                }
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        match cursor
+            .position_in(bounds)
+            .and_then(|p| self.bar_at(p, bounds.size()))
+            .and_then(|i| self.bars[i].game)
+        {
+            Some(_) => mouse::Interaction::Pointer,
+            None => mouse::Interaction::default(),
+        }
+    }
+}
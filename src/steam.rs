@@ -7,26 +7,30 @@ pub struct SteamRunner {
 }
 
 impl Runner for SteamRunner {
-    fn get_command(&self) -> Command {
-        Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
+        Ok(Command {
             program: "steam".to_owned(),
             args: vec![format!("steam://rungameid/{}", self.path)],
             envs: std::collections::HashMap::new(),
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["steam".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    /// the path to the game is its AppID
+    fn uses_path(&self) -> bool {
+        false
+    }
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "steam" => Some(Command {
+            "steam" => Ok(Command {
                 program: "steam".to_owned(),
                 args: vec!["steam://open/main".to_owned()],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
@@ -0,0 +1,171 @@
+//! Box art thumbnails, decoded in the background and cached on disk (in `thumbnails` in the
+//! cache directory) so that later starts don't need to decode the full images again.
+//!
+//! Thumbnails are decoded at the size the box arts are shown at in physical pixels, so that they
+//! stay sharp on HiDPI screens. That size is rounded up to `THUMBNAIL_STEP`, so that resizing the
+//! box arts only decodes them again once in a while.
+//!
+//! Cached thumbnails are raw RGBA pixels preceded by a hash of the modification time and size of
+//! the source image and of the thumbnail width, so that changing the image invalidates them, and
+//! by their width and height. They are named after a hash of the path of the source image, such
+//! that each image only has one, and those of images no game uses are removed on start.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use iced::widget::image::Handle;
+
+use crate::Message;
+
+/// thumbnail widths are multiples of this, in physical pixels
+const THUMBNAIL_STEP: u32 = 100;
+
+/// shown until the thumbnail is loaded, and for games without box art. Every game shares the
+/// same handle.
+pub fn placeholder() -> Handle {
+    static PLACEHOLDER: once_cell::sync::Lazy<Handle> =
+        once_cell::sync::Lazy::new(|| handle(image::RgbaImage::new(1, 1)));
+    PLACEHOLDER.clone()
+}
+
+fn handle(thumbnail: image::RgbaImage) -> Handle {
+    Handle::from_rgba(thumbnail.width(), thumbnail.height(), thumbnail.into_raw())
+}
+
+/// 64 bits FNV-1a, which unlike `DefaultHasher` gives the same result across Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// the height of a thumbnail `width` wide, box arts being 2:3
+fn thumbnail_height(width: u32) -> u32 {
+    width * crate::IMAGE_HEIGHT / crate::IMAGE_WIDTH
+}
+
+fn cache_name(box_art: &Path) -> String {
+    format!("{:016x}", fnv1a(box_art.to_string_lossy().as_bytes()))
+}
+
+/// where the thumbnail of `box_art` is cached, along with the key of the thumbnail it should hold
+fn cache_path(box_art: &Path, width: u32) -> Option<(PathBuf, u64)> {
+    let meta = std::fs::metadata(box_art).ok()?;
+    let mtime = meta
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    let key = format!(
+        "{}.{}\n{}\n{width}",
+        mtime.as_secs(),
+        mtime.subsec_nanos(),
+        meta.len()
+    );
+    Some((
+        crate::DIRS
+            .cache_dir()
+            .join("thumbnails")
+            .join(cache_name(box_art)),
+        fnv1a(key.as_bytes()),
+    ))
+}
+
+fn read_cached((path, key): &(PathBuf, u64)) -> Option<image::RgbaImage> {
+    let data = std::fs::read(path).ok()?;
+    if u64::from_le_bytes(data.get(0..8)?.try_into().ok()?) != *key {
+        return None;
+    }
+    let width = u32::from_le_bytes(data.get(8..12)?.try_into().ok()?);
+    let height = u32::from_le_bytes(data.get(12..16)?.try_into().ok()?);
+    image::RgbaImage::from_raw(width, height, data[16..].to_vec())
+}
+
+fn write_cached((path, key): &(PathBuf, u64), thumbnail: &image::RgbaImage) -> std::io::Result<()> {
+    let mut data = Vec::with_capacity(16 + thumbnail.as_raw().len());
+    data.extend_from_slice(&key.to_le_bytes());
+    data.extend_from_slice(&thumbnail.width().to_le_bytes());
+    data.extend_from_slice(&thumbnail.height().to_le_bytes());
+    data.extend_from_slice(thumbnail.as_raw());
+    std::fs::write(path, data)
+}
+
+/// the thumbnail of `box_art` fitting in `width` pixels wide, from the cache if possible. Fails if
+/// the image can't be decoded.
+pub fn load(box_art: &str, width: u32) -> Result<image::RgbaImage, String> {
+    let cached = cache_path(Path::new(box_art), width);
+    if let Some(t) = cached.as_ref().and_then(read_cached) {
+        return Ok(t);
+    }
+
+    // a missing box art is reported by the doctor, it doesn't make the game broken
+    let Ok(reader) = image::io::Reader::open(box_art) else {
+        log::warn!("couldn't open box art {box_art}");
+        return Ok(image::RgbaImage::new(width, thumbnail_height(width)));
+    };
+    let thumbnail = reader
+        .decode()
+        .map_err(|e| format!("couldn't decode box art {box_art} : {e}"))?
+        .thumbnail(width, thumbnail_height(width))
+        .to_rgba8();
+
+    if let Some(c) = cached {
+        if let Err(e) = write_cached(&c, &thumbnail) {
+            log::warn!("couldn't cache the thumbnail of {box_art} : {e}");
+        }
+    }
+    Ok(thumbnail)
+}
+
+/// removes the cached thumbnails of the images that aren't in `box_arts`
+pub fn prune<'a>(box_arts: impl Iterator<Item = &'a str>) {
+    let used = box_arts
+        .map(|a| cache_name(Path::new(a)))
+        .collect::<std::collections::HashSet<_>>();
+    let Ok(entries) = std::fs::read_dir(crate::DIRS.cache_dir().join("thumbnails")) else {
+        return;
+    };
+    for e in entries.filter_map(|a| a.ok()) {
+        if !used.contains(&e.file_name().to_string_lossy()[..]) {
+            if let Err(err) = std::fs::remove_file(e.path()) {
+                log::warn!("couldn't remove cached thumbnail {:?} : {err}", e.path());
+            }
+        }
+    }
+}
+
+/// the width thumbnails should have for box arts shown `width` physical pixels wide
+pub fn width_for(width: f32) -> u32 {
+    (width / THUMBNAIL_STEP as f32).ceil().max(1.) as u32 * THUMBNAIL_STEP
+}
+
+/// loads the thumbnails of `jobs`, pairs of a game id and its box art, on as many threads as
+/// there are cores, sending a `ThumbnailLoaded` for each one
+pub fn load_all(jobs: Vec<(String, String)>, width: u32) -> iced::Task<Message> {
+    if jobs.is_empty() {
+        return iced::Task::none();
+    }
+
+    let threads = std::thread::available_parallelism()
+        .map(|a| a.get())
+        .unwrap_or(1)
+        .min(jobs.len());
+    let queue = Arc::new(Mutex::new(jobs.into_iter()));
+    let (sender, receiver) = iced::futures::channel::mpsc::unbounded();
+    for _ in 0..threads {
+        let queue = queue.clone();
+        let sender = sender.clone();
+        std::thread::spawn(move || loop {
+            let Some((id, box_art)) = queue.lock().unwrap().next() else {
+                break;
+            };
+            let thumbnail = load(&box_art, width).map(handle);
+            if sender.unbounded_send((id, box_art, thumbnail)).is_err() {
+                break;
+            }
+        });
+    }
+    iced::Task::run(receiver, move |(id, box_art, thumbnail)| {
+        Message::ThumbnailLoaded(id, box_art, width, thumbnail)
+    })
+}
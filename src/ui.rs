@@ -13,6 +13,31 @@ use iced::{Length, Theme};
 use iced_aw::{TabBar, TabLabel};
 use iced_fonts::NERD_FONT;
 
+/// the scrollable of the games grid and list, scrolled by the keyboard and gamepad navigation
+pub static GRID_ID: once_cell::sync::Lazy<iced::widget::scrollable::Id> =
+    once_cell::sync::Lazy::new(iced::widget::scrollable::Id::unique);
+
+/// how much bigger the box arts are in couch mode
+const COUCH_SCALE: f32 = 1.6;
+
+/// the size of the box arts of the games grid, in logical pixels
+pub fn cover_size(mg: &crate::MainGUI) -> iced::Size {
+    let scale = if mg.couch_mode { COUCH_SCALE } else { 1. };
+    let width = mg.cover_width * scale;
+    iced::Size::new(width, width * IMAGE_HEIGHT as f32 / IMAGE_WIDTH as f32)
+}
+
+/// the size of the cells of the games grid : the box art, the name and the button padding. Every
+/// cell has the same size, so that the visible rows can be found without laying out the others.
+pub fn cell_size(mg: &crate::MainGUI) -> iced::Size {
+    let cover = cover_size(mg);
+    iced::Size::new(cover.width + 20., cover.height + 40.)
+}
+
+/// the search box of the games grid, focused with Ctrl+F
+pub static SEARCH_ID: once_cell::sync::Lazy<iced::widget::text_input::Id> =
+    once_cell::sync::Lazy::new(iced::widget::text_input::Id::unique);
+
 // struct ButtonStyle();
 // impl iced::widget::button::StyleSheet for ButtonStyle {
 //     type Style = Theme;
@@ -95,6 +120,9 @@ pub fn get_widget(
             // .into(),
         ]
         .height(Length::Fixed(WIDGET_HEIGHT as f32)),
+        config::CValue::StrArr(arr) if k == "wine:dll_overrides" => {
+            dll_overrides_widget(arr.clone(), label, k, uses_default)
+        }
         config::CValue::StrArr(arr) => {
             // log::error!("Feature StrArr() not yet available in config display");
             let mut col = Vec::new();
@@ -313,7 +341,569 @@ pub fn get_widget(
     .align_y(Alignment::Center)
 }
 
+/// the groups of games the grid can be restricted to
+fn sidebar(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
+    use crate::collections::Group;
+
+    let entry = |label: String, group: Option<Group>| -> iced::Element<'_, Message> {
+        let selected = mg.filter.group == group;
+        iced::widget::button(text(label))
+            .on_press(Message::SetFilter(crate::filter::Filter {
+                group,
+                ..mg.filter.clone()
+            }))
+            .style(if selected {
+                button::primary
+            } else {
+                button::text
+            })
+            .width(Length::Fill)
+            .into()
+    };
+
+    let (tags, collections) = crate::collections::tags_and_collections(&mg.games);
+    let mut out = column![
+        entry("all games".to_owned(), None),
+        entry("favorites".to_owned(), Some(Group::Favorites)),
+        entry("hidden".to_owned(), Some(Group::Hidden)),
+    ];
+    for (title, entries) in [
+        (
+            "tags",
+            tags.into_iter()
+                .map(|a| (a.clone(), Group::Tag(a)))
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "collections",
+            collections
+                .into_iter()
+                .map(|a| (a.clone(), Group::Collection(a)))
+                .collect(),
+        ),
+        (
+            "smart collections",
+            mg.smart_collections
+                .iter()
+                .map(|a| (a.name.clone(), Group::Smart(a.clone())))
+                .collect(),
+        ),
+    ] {
+        if !entries.is_empty() {
+            out = out.push(text(title).style(text::primary));
+            for (label, group) in entries {
+                out = out.push(entry(label, Some(group)));
+            }
+        }
+    }
+    iced::widget::scrollable(out.spacing(5))
+        .width(Length::Fixed(180.))
+        .into()
+}
+
+/// the search box and quick filters shown above the games grid
+fn search_bar(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
+    const ALL_RUNNERS: &str = "all runners";
+    let mut runners = mg
+        .games
+        .iter()
+        .map(|g| g.runner_id.clone())
+        .collect::<Vec<_>>();
+    runners.sort();
+    runners.dedup();
+    runners.insert(0, ALL_RUNNERS.to_owned());
+
+    let filter = &mg.filter;
+    row![
+        iced::widget::button(text("󰍜").font(NERD_FONT)).on_press(Message::ToggleSidebar),
+        iced::widget::text_input("search (Ctrl+F)", &mg.search)
+            .id(SEARCH_ID.clone())
+            .on_input(Message::SearchChanged)
+            .on_submit(Message::LaunchTopResult)
+            .width(Length::FillPortion(3)),
+        iced::widget::pick_list(
+            runners,
+            Some(filter.runner.clone().unwrap_or(ALL_RUNNERS.to_owned())),
+            move |a| Message::SetFilter(crate::filter::Filter {
+                runner: (a != ALL_RUNNERS).then_some(a),
+                ..filter.clone()
+            })
+        ),
+        iced::widget::checkbox("running", filter.running).on_toggle(move |a| {
+            Message::SetFilter(crate::filter::Filter {
+                running: a,
+                ..filter.clone()
+            })
+        }),
+        iced::widget::checkbox("no box art", filter.no_box_art).on_toggle(move |a| {
+            Message::SetFilter(crate::filter::Filter {
+                no_box_art: a,
+                ..filter.clone()
+            })
+        }),
+        iced::widget::checkbox("path missing", filter.path_missing).on_toggle(move |a| {
+            Message::SetFilter(crate::filter::Filter {
+                path_missing: a,
+                ..filter.clone()
+            })
+        }),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// the box arts of the games shown, of which only the visible rows are built
+fn games_grid(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
+    let cell = cell_size(mg);
+    let colors = *mg.colors();
+    let selected = mg.selected;
+
+    let mut grid: crate::grid_widget::Grid<Message, _> =
+        crate::grid_widget::Grid::with_column_width(cell.width);
+    let visible = crate::filter::visible(&mg.games, &mg.search, &mg.filter);
+    let window = crate::grid_widget::visible_window(visible.len(), cell, mg.grid_viewport.as_ref());
+    for i in visible[window.first..window.last].iter().copied() {
+        let g = &mg.games[i];
+        grid.insert::<iced::Element<'_, Message>>(
+            iced::widget::Container::new(
+                iced::widget::button(
+                    iced::widget::column(vec![
+                        iced::widget::image(g.image.clone())
+                            .filter_method(FilterMethod::Linear)
+                            .width(Length::Fixed(cell.width - 20.))
+                            .height(Length::Fixed(cell.height - 40.))
+                            .into(),
+                        iced::widget::text(g.name.clone())
+                            .wrapping(text::Wrapping::None)
+                            .into(),
+                    ])
+                    // .padding(iced::Padding::from(0.5))
+                    .align_x(iced::Alignment::Center),
+                )
+                .on_press(Message::GameSelected(i))
+                .style(move |_theme: &Theme, status| {
+                    if Some(i) != selected {
+                        if let iced::widget::button::Status::Hovered = status {
+                            let mut out =
+                                button::Style::default().with_background(colors.card_hovered);
+                            out.text_color = colors.card_text;
+                            out
+                        } else {
+                            let mut out =
+                                button::Style::default().with_background(iced::Color::TRANSPARENT);
+                            out.text_color = colors.card_text;
+                            out
+                        }
+                    } else {
+                        let mut out =
+                            button::Style::default().with_background(colors.card_selected);
+                        out.text_color = colors.card_selected_text;
+                        out
+                    }
+                }), // .height(Length::Fixed(600.))
+            )
+            .padding(iced::Padding::from(0))
+            .height(Length::Fixed(cell.height))
+            .clip(true)
+            .into(), // .into(),
+        );
+    }
+    column![
+        iced::widget::Space::with_height(window.above),
+        grid,
+        iced::widget::Space::with_height(window.below),
+    ]
+    .into()
+}
+
+/// a searchable list of the verbs known to winetricks, clicking one adds it to `verbs`
+fn winetricks_picker(mg: &crate::MainGUI, verbs: Vec<String>) -> iced::Element<'_, Message> {
+    let toggle = iced::widget::button(text(if mg.winetricks_picker {
+        "hide winetricks verbs"
+    } else {
+        "browse winetricks verbs"
+    }))
+    .on_press(Message::ToggleWinetricksPicker);
+    if !mg.winetricks_picker {
+        return toggle.into();
+    }
+
+    let list: iced::Element<'_, Message> = match &mg.winetricks_verbs {
+        None => text("listing the verbs...").into(),
+        Some(Err(e)) => text(e.clone()).style(text::danger).into(),
+        Some(Ok(all)) => {
+            let mut col = column![].spacing(2);
+            // the full list has hundreds of verbs
+            for v in crate::winetricks::search(all, &mg.winetricks_search).take(200) {
+                let added = verbs.contains(&v.name);
+                let mut new = verbs.clone();
+                new.push(v.name.clone());
+                col = col.push(
+                    row![
+                        iced::widget::button(text(v.name.clone()))
+                            .style(entry_style(added))
+                            .on_press_maybe((!added).then(|| {
+                                Message::SettingChanged(
+                                    "wine:winetricks_verbs".to_owned(),
+                                    CValue::StrArr(new),
+                                )
+                            }))
+                            .width(Length::Fixed(200.)),
+                        text(format!("{} ({})", v.description, v.category)),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                );
+            }
+            iced::widget::scrollable(col)
+                .height(Length::Fixed(300.))
+                .into()
+        }
+    };
+    column![
+        toggle,
+        iced::widget::text_input("search verbs", &mg.winetricks_search)
+            .on_input(Message::WinetricksSearchChanged),
+        list,
+    ]
+    .spacing(5)
+    .into()
+}
+
+/// a table of dll overrides, each row being a dll and its load order. The entries are stored as
+/// `dll=mode`.
+fn dll_overrides_widget<'a>(
+    entries: Vec<String>,
+    label: String,
+    k: String,
+    uses_default: bool,
+) -> iced::widget::Row<'a, Message> {
+    use crate::dll_overrides::{entry, Mode};
+    // these would end the entry, several dlls being separated by commas
+    let clean = |a: String| a.replace(['=', ';'], "");
+    let changed = {
+        let k = k.clone();
+        move |e: Vec<String>| Message::SettingChanged(k.clone(), CValue::StrArr(e))
+    };
+
+    let mut col = column![];
+    for (i, e) in entries.iter().enumerate() {
+        let (dll, mode) = match e.split_once('=') {
+            Some((dll, mode)) => (dll.to_owned(), Mode::from_code(mode)),
+            None => (e.clone(), None),
+        };
+        let rename = {
+            let (entries, changed) = (entries.clone(), changed.clone());
+            move |a: String| {
+                let mut new = entries.clone();
+                new[i] = entry(&clean(a), mode.unwrap_or(Mode::NativeBuiltin));
+                changed(new)
+            }
+        };
+        let set_mode = {
+            let (entries, changed, dll) = (entries.clone(), changed.clone(), dll.clone());
+            move |m| {
+                let mut new = entries.clone();
+                new[i] = entry(&dll, m);
+                changed(new)
+            }
+        };
+        let mut removed = entries.clone();
+        removed.remove(i);
+        col = col.push(
+            row![
+                iced::widget::text_input("dll", &dll)
+                    .on_input(rename)
+                    .width(Length::FillPortion(5)),
+                iced::widget::pick_list(Mode::ALL, mode, set_mode)
+                    .placeholder("invalid")
+                    .width(Length::FillPortion(4)),
+                iced::widget::button("x")
+                    .on_press(changed(removed))
+                    .width(Length::FillPortion(1)),
+            ]
+            .height(Length::Fixed(WIDGET_HEIGHT as f32)),
+        );
+    }
+
+    row![
+        iced::widget::text(label).width(Length::FillPortion(3)),
+        col.width(Length::FillPortion(3)),
+        iced::widget::toggler(uses_default)
+            .on_toggle(move |a| Message::SettingDefaultChanged(k.clone(), a))
+            .width(Length::FillPortion(1)),
+    ]
+}
+
+/// a field to add an override to `wine:dll_overrides`, once submitted
+fn dll_override_adder(mg: &crate::MainGUI, entries: Vec<String>) -> iced::Element<'_, Message> {
+    let add = Message::AddDllOverride(entries);
+    row![
+        iced::widget::Space::with_width(Length::FillPortion(3)),
+        row![
+            iced::widget::text_input("add a dll, e.g. dinput8", &mg.dll_override_draft)
+                .on_input(Message::DllOverrideDraftChanged)
+                .on_submit(add.clone())
+                .width(Length::FillPortion(9)),
+            iced::widget::button("add")
+                .on_press(add)
+                .width(Length::FillPortion(1)),
+        ]
+        .width(Length::FillPortion(3)),
+        iced::widget::Space::with_width(Length::FillPortion(1)),
+    ]
+    .height(Length::Fixed(WIDGET_HEIGHT as f32))
+    .into()
+}
+
+/// a list of the wine builds found, picking one sets it as `wine:wine_build`
+fn wine_build_picker(mg: &crate::MainGUI, current: String) -> iced::Element<'_, Message> {
+    let names = mg
+        .wine_builds
+        .iter()
+        .map(|a| a.name.clone())
+        .collect::<Vec<_>>();
+    let details = match mg.wine_builds.iter().find(|a| a.name == current) {
+        Some(b) => text(format!(
+            "{}{} in {}",
+            b.version.clone().unwrap_or("unknown version".to_owned()),
+            if b.proton { " (proton)" } else { "" },
+            b.root.display()
+        )),
+        None if current.is_empty() => text(""),
+        None => text(format!("{current} wasn't found")).style(text::danger),
+    };
+    row![
+        iced::widget::pick_list(names, (!current.is_empty()).then_some(current), |a| {
+            Message::SettingChanged("wine:wine_build".to_owned(), CValue::Str(a))
+        })
+        .placeholder("pick a wine build"),
+        iced::widget::button(text("rescan")).on_press(Message::RescanWineBuilds),
+        details,
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// the height of a row of the list view
+pub const LIST_ROW_HEIGHT: f32 = 32.;
+
+/// the style of the entries of the list and detail views
+fn entry_style(selected: bool) -> fn(&Theme, button::Status) -> button::Style {
+    if selected {
+        button::primary
+    } else {
+        button::text
+    }
+}
+
+fn format_date(t: std::time::SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(t)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// a table of the games shown, sorted by clicking its headers. Only the visible rows are built.
+fn games_list(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
+    use crate::sort::Sorts;
+
+    let header = |label: &str, sort: Option<Sorts>, portion: u16| -> iced::Element<'_, Message> {
+        let mut content = row![text(label.to_owned())].spacing(5);
+        if sort == Some(mg.sort_alg) {
+            content =
+                content.push(text(if mg.sort_descending { "󰒽" } else { "󰒼" }).font(NERD_FONT));
+        }
+        let b = iced::widget::button(content)
+            .style(button::text)
+            .width(Length::FillPortion(portion));
+        match sort {
+            Some(s) => b.on_press(Message::SortByColumn(s)).into(),
+            None => b.into(),
+        }
+    };
+    let cell = |content: String, portion: u16| -> iced::Element<'_, Message> {
+        text(content)
+            .wrapping(text::Wrapping::None)
+            .width(Length::FillPortion(portion))
+            .into()
+    };
+
+    let visible = crate::filter::visible(&mg.games, &mg.search, &mg.filter);
+    // the header counts as the first row
+    let window = crate::grid_widget::visible_window(
+        visible.len() + 1,
+        iced::Size::new(f32::INFINITY, LIST_ROW_HEIGHT),
+        mg.grid_viewport.as_ref(),
+    );
+    let mut out = column![iced::widget::Space::with_height(window.above)];
+    for k in window.first..window.last {
+        let row: iced::Element<'_, Message> = if k == 0 {
+            row![
+                header("name", Some(Sorts::Name), 4),
+                header("runner", Some(Sorts::Runner), 2),
+                header("year", Some(Sorts::ReleaseDate), 1),
+                header("playtime", Some(Sorts::Playtime), 2),
+                header("last played", Some(Sorts::LastPlayed), 2),
+                header("path", None, 1),
+            ]
+            .into()
+        } else {
+            let i = visible[k - 1];
+            let g = &mg.games[i];
+            let missing = crate::doctor::path_missing(g);
+            iced::widget::button(
+                row![
+                    cell(g.name.clone(), 4),
+                    cell(g.runner_id.clone(), 2),
+                    cell(g.release_year.map(|a| a.to_string()).unwrap_or_default(), 1),
+                    cell(crate::stats::short_duration(g.playtime.total), 2),
+                    cell(
+                        g.playtime
+                            .last_played
+                            .map(format_date)
+                            .unwrap_or("never".to_owned()),
+                        2
+                    ),
+                    text(if missing { "missing" } else { "ok" })
+                        .style(if missing { text::danger } else { text::success })
+                        .width(Length::FillPortion(1)),
+                ]
+                .spacing(10),
+            )
+            .style(entry_style(mg.selected == Some(i)))
+            .on_press(Message::GameSelected(i))
+            .into()
+        };
+        out = out.push(
+            iced::widget::Container::new(row)
+                .height(Length::Fixed(LIST_ROW_HEIGHT))
+                .clip(true),
+        );
+    }
+    out.push(iced::widget::Space::with_height(window.below))
+        .into()
+}
+
+/// the names of the games shown next to the box art, details and actions of the selected one
+fn game_detail(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
+    let mut names = column![];
+    for i in crate::filter::visible(&mg.games, &mg.search, &mg.filter) {
+        names = names.push(
+            iced::widget::button(text(mg.games[i].name.clone()))
+                .style(entry_style(mg.selected == Some(i)))
+                .on_press(Message::GameSelected(i))
+                .width(Length::Fill),
+        );
+    }
+
+    let details: iced::Element<'_, Message> = if let Some(i) = mg.selected {
+        let g = &mg.games[i];
+        // the full image rather than the thumbnail
+        let hero = match &g.box_art {
+            Some(b) => iced::widget::image(iced::widget::image::Handle::from_path(b)),
+            None => iced::widget::image(g.image.clone()),
+        }
+        .filter_method(FilterMethod::Linear)
+        .height(Length::Fixed(2. * IMAGE_HEIGHT as f32));
+
+        let mut actions = row![if g.is_running {
+            iced::widget::button(text("kill")).on_press(Message::KillSelected)
+        } else {
+            iced::widget::button(text("run")).on_press(Message::RunSelected)
+        }]
+        .spacing(10);
+        for s in g.get_subcommands() {
+            actions = actions.push(
+                iced::widget::button(text(s.clone())).on_press(Message::RunSubcommandSelected(s)),
+            );
+        }
+
+        let mut info = column![
+            text(g.name.clone()).size(30),
+            text(format!("runner : {}", g.runner_id)),
+        ]
+        .spacing(5);
+        if let Some(y) = g.release_year {
+            info = info.push(text(format!("release year : {y}")));
+        }
+        info = info.push(text(format!("path : {}", g.path_to_game.display())));
+        if !g.tags.is_empty() {
+            info = info.push(text(format!("tags : {}", g.tags.join(", "))));
+        }
+        if !g.collections.is_empty() {
+            info = info.push(text(format!("collections : {}", g.collections.join(", "))));
+        }
+        info = info
+            .push(text(format!(
+                "played {} ({} this year, {} in the last 30 days)",
+                crate::duration_to_string(g.playtime.total),
+                crate::stats::short_duration(g.playtime.this_year),
+                crate::stats::short_duration(g.playtime.last_30_days),
+            )))
+            .push(text(format!(
+                "last played : {}",
+                g.playtime
+                    .last_played
+                    .map(format_date)
+                    .unwrap_or("never".to_owned())
+            )));
+
+        column![row![hero, info].spacing(20), actions]
+            .spacing(20)
+            .into()
+    } else {
+        text("No game selected").into()
+    };
+
+    row![
+        iced::widget::scrollable(names).width(Length::Fixed(250.)),
+        iced::widget::scrollable(details).width(Length::Fill),
+    ]
+    .spacing(20)
+    .height(Length::Fill)
+    .into()
+}
+
+/// large box arts and hints for the gamepad, without anything that needs a mouse
+fn couch_view(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
+    let status = match mg.selected.map(|i| &mg.games[i]) {
+        Some(g) => format!(
+            "{}  ·  {}{}",
+            g.name,
+            crate::duration_to_string(g.playtime.total),
+            if g.is_running { "  ·  running" } else { "" }
+        ),
+        None => String::new(),
+    };
+    column![
+        iced::widget::scrollable(games_grid(mg))
+            .id(GRID_ID.clone())
+            .on_scroll(|v| Message::GridScrolled(Some(v)))
+            .height(Length::Fill),
+        row![
+            text(status).size(24),
+            iced::widget::Space::with_width(Length::Fill),
+            text(
+                "A / Enter : play    X : kill    B / Esc : back    Start / F11 : leave couch mode"
+            )
+            .size(18)
+            .style(text::primary),
+        ]
+        .align_y(Alignment::Center),
+    ]
+    .spacing(20)
+    .padding(40)
+    .into()
+}
+
 pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
+    if mg.couch_mode {
+        return couch_view(mg);
+    }
+
+    let colors = *mg.colors();
     let mut top_bar: iced_aw::widgets::TabBar<Message, crate::GridStatus> =
         TabBar::new(Message::SetGridStatus)
             // .style(TabBarStyles::Blue)
@@ -329,33 +919,36 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                 crate::GridStatus::AddGame,
                 TabLabel::Text("Add Game".to_owned()),
             )
+            .push(crate::GridStatus::Stats, TabLabel::Text("Stats".to_owned()))
             .set_active_tab(&mg.grid_status)
-            .width(Length::FillPortion(3))
-            .style(|theme: &Theme, status| {
-                let pal = theme.extended_palette();
-                match status {
-                    iced_aw::card::Status::Active => iced_aw::style::tab_bar::Style {
-                        text_color: pal.primary.strong.text,
-                        tab_label_background: iced::Background::Color(pal.primary.strong.color),
-                        tab_label_border_width: 0.,
-                        ..iced_aw::style::tab_bar::Style::default()
-                    },
-                    iced_aw::card::Status::Hovered => iced_aw::style::tab_bar::Style {
-                        text_color: pal.primary.base.text,
-                        tab_label_background: iced::Background::Color(pal.primary.base.color),
-                        tab_label_border_width: 0.,
-                        ..iced_aw::style::tab_bar::Style::default()
-                    },
-                    iced_aw::card::Status::Disabled => iced_aw::style::tab_bar::Style {
-                        text_color: iced::Color::WHITE,
-                        tab_label_background: iced::Background::Color(pal.background.strong.color),
-                        tab_label_border_width: 0.,
-                        ..iced_aw::style::tab_bar::Style::default()
-                    },
-                    _ => iced_aw::style::tab_bar::Style::default(),
+            .width(Length::FillPortion(4))
+            .style(move |_theme: &Theme, status| {
+                let (text_color, background) = match status {
+                    iced_aw::card::Status::Active => {
+                        (colors.tab_active_text, colors.tab_active_background)
+                    }
+                    iced_aw::card::Status::Hovered => {
+                        (colors.tab_hovered_text, colors.tab_hovered_background)
+                    }
+                    _ => (colors.tab_text, colors.tab_background),
+                };
+                iced_aw::style::tab_bar::Style {
+                    text_color,
+                    tab_label_background: iced::Background::Color(background),
+                    tab_label_border_width: 0.,
+                    ..iced_aw::style::tab_bar::Style::default()
                 }
             });
 
+    if !mg.broken.is_empty() {
+        top_bar = top_bar
+            .push(
+                crate::GridStatus::BrokenEntries,
+                TabLabel::Text(format!("Broken Entries ({})", mg.broken.len())),
+            )
+            .set_active_tab(&mg.grid_status);
+    }
+
     if mg.selected.is_some() {
         top_bar = top_bar
             .push(
@@ -364,7 +957,7 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
             )
             .push(crate::GridStatus::Logs, TabLabel::Text("Logs".to_owned()))
             .set_active_tab(&mg.grid_status)
-            .width(Length::FillPortion(5));
+            .width(Length::FillPortion(6));
     }
 
     let run_module: iced::Element<'_, Message> = if let Some(g) = mg.selected {
@@ -394,10 +987,50 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
         iced::widget::Space::with_width(Length::FillPortion(2)).into()
     };
 
+    let sort_module: iced::Element<'_, Message> = if mg.grid_status == crate::GridStatus::GamesGrid
+    {
+        row![
+            iced::widget::pick_list(
+                crate::sort::Sorts::ALL,
+                Some(mg.sort_alg),
+                Message::SortChanged
+            ),
+            iced::widget::button(text(if mg.sort_descending { "󰒽" } else { "󰒼" }).font(NERD_FONT))
+                .on_press(Message::ToggleSortOrder),
+            iced::widget::pick_list(
+                crate::views::LibraryView::ALL,
+                Some(mg.library_view),
+                Message::LibraryViewChanged
+            ),
+        ]
+        .push_maybe(
+            (mg.library_view == crate::views::LibraryView::Grid).then(|| {
+                iced::widget::slider(
+                    crate::views::COVER_WIDTHS,
+                    mg.cover_width,
+                    Message::CoverWidthChanged,
+                )
+                .step(10.)
+                .on_release(Message::SaveCoverWidth)
+                .width(Length::Fixed(100.))
+            }),
+        )
+        .spacing(5)
+        .align_y(Alignment::Center)
+        .into()
+    } else {
+        iced::widget::Space::with_width(Length::Shrink).into()
+    };
+
     let top_bar = iced::widget::Row::with_children(vec![
         top_bar.into(),
+        sort_module,
         if let Some(i) = mg.selected {
-            text(crate::duration_to_string(mg.games[i].time_played)).into()
+            if let Some(e) = &mg.games[i].launch_error {
+                text(e.to_string()).style(text::danger).into()
+            } else {
+                text(crate::duration_to_string(mg.games[i].playtime.total)).into()
+            }
         } else {
             iced::widget::Space::with_width(Length::FillPortion(2)).into()
         },
@@ -406,72 +1039,12 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
     .width(Length::Fill);
     // .align_items(iced::Alignment::End);
 
-    // let choose_theme = [crate::ThemeType::Light, crate::ThemeType::Dark]
-    //     .iter()
-    //     .fold(column!["Choose a theme:"].spacing(10), |column, option| {
-    //         column.push(radio(
-    //             format!("{:?}", option),
-    //             *option,
-    //             Some(*option),
-    //             Message::ThemeChanged,
-    //         ))
-    //     });
-
     let ge: iced::Element<'_, Message> = match mg.grid_status {
-        crate::GridStatus::GamesGrid => {
-            let image_size = IMAGE_WIDTH as u16;
-
-            let mut grid: crate::grid_widget::Grid<Message, _> =
-                crate::grid_widget::Grid::with_column_width(image_size as f32 + 20.);
-            for (i, g) in mg.games.iter().enumerate() {
-                grid.insert::<iced::Element<'_, Message>>(
-                    iced::widget::Container::new(
-                        iced::widget::button(
-                            iced::widget::column(vec![
-                                iced::widget::image(iced::widget::image::Handle::from_rgba(
-                                    g.image.width(),
-                                    g.image.height(),
-                                    g.image.as_raw().clone(),
-                                ))
-                                .filter_method(FilterMethod::Linear)
-                                .width(Length::Fixed(image_size as f32))
-                                // .height(Length::Fixed(IMAGE_HEIGHT as f32))
-                                .into(),
-                                iced::widget::text(g.name.clone()).into(),
-                            ])
-                            // .padding(iced::Padding::from(0.5))
-                            .align_x(iced::Alignment::Center),
-                        )
-                        .on_press(Message::GameSelected(i))
-                        .style(move |theme: &Theme, status| {
-                            let palette = theme.extended_palette();
-                            if Some(i) != mg.selected {
-                                if let iced::widget::button::Status::Hovered = status {
-                                    let mut out = button::Style::default()
-                                        .with_background(palette.background.strong.color);
-                                    out.text_color = iced::Color::WHITE;
-                                    out
-                                } else {
-                                    let mut out = button::Style::default()
-                                        .with_background(iced::Color::TRANSPARENT);
-                                    out.text_color = iced::Color::WHITE;
-                                    out
-                                }
-                            } else {
-                                let mut out = button::Style::default()
-                                    .with_background(palette.primary.strong.color);
-                                out.text_color = iced::Color::BLACK;
-                                out
-                            }
-                        }), // .height(Length::Fixed(600.))
-                    )
-                    .padding(iced::Padding::from(0))
-                    // .height(Length::Shrink)
-                    .into(), // .into(),
-                );
-            }
-            grid.into()
-        }
+        crate::GridStatus::GamesGrid => match mg.library_view {
+            crate::views::LibraryView::Grid => games_grid(mg),
+            crate::views::LibraryView::List => games_list(mg),
+            crate::views::LibraryView::Detail => game_detail(mg),
+        },
         crate::GridStatus::GamesSettings => {
             let mut options = column![];
 
@@ -487,7 +1060,7 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                     for k in cat {
                         let i = mg.default_config.get(&k).unwrap(); //expect(&format!("{k}")[..]);
                         let s = k.split(':').collect::<Vec<_>>();
-                        if s.len() == 1 || s[0] == runner {
+                        if (s.len() == 1 || s[0] == runner) && k != "id" {
                             let (v, uses_default) =
                                 if let Some(v) = mg.temp_settings.as_ref().unwrap().0.get(&k) {
                                     (v, false)
@@ -495,7 +1068,24 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                                     (&i.1, true)
                                 };
                             let label = i.0.clone() + " : ";
-                            options = options.push(get_widget(v, label, k, uses_default))
+                            let verbs = (k == "wine:winetricks_verbs").then(|| v.as_strarr());
+                            let build = (k == "wine:wine_build").then(|| v.as_string());
+                            let dlls = (k == "wine:dll_overrides").then(|| v.as_strarr());
+                            options = options.push(get_widget(v, label, k, uses_default));
+                            if let Some(build) = build {
+                                options = options.push(wine_build_picker(mg, build));
+                            }
+                            if let Some(dlls) = dlls {
+                                options = options.push(dll_override_adder(mg, dlls));
+                            }
+                            if let Some(verbs) = verbs {
+                                options = options.push(winetricks_picker(mg, verbs));
+                            }
+                        }
+                    }
+                    if s.len() == 2 {
+                        for (label, value) in &mg.runner_info {
+                            options = options.push(text(format!("{label} : {value}")));
                         }
                     }
                 }
@@ -509,10 +1099,41 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                         .on_press(Message::ToggleSettings),
                     iced::widget::button(iced::widget::text("Ok"))
                         .on_press(Message::ApplyCloseSettings),
+                    iced::widget::Space::with_width(Length::Fill),
+                    iced::widget::button(iced::widget::text("Check"))
+                        .on_press(Message::CheckSelected),
+                    iced::widget::button(iced::widget::text("Duplicate"))
+                        .on_press(Message::DuplicateSelected),
+                    iced::widget::button(iced::widget::text(
+                        if mg.games[mg.selected.unwrap()].hidden {
+                            "Unhide"
+                        } else {
+                            "Hide"
+                        }
+                    ))
+                    .on_press(Message::ToggleHiddenSelected),
+                    iced::widget::button(iced::widget::text("Delete"))
+                        .style(button::danger)
+                        .on_press_maybe(
+                            (!mg.games[mg.selected.unwrap()].is_running)
+                                .then_some(Message::DeleteSelected)
+                        ),
                 ]
                 .align_y(iced::Alignment::End),
             );
 
+            if let Some(report) = &mg.doctor_report {
+                if report.is_empty() {
+                    options = options.push(text("No problem found").style(text::success));
+                }
+                for p in report {
+                    options = options.push(text(p.to_string()).style(match p.severity {
+                        crate::doctor::Severity::Warning => text::primary,
+                        crate::doctor::Severity::Error => text::danger,
+                    }));
+                }
+            }
+
             options.into()
         }
         crate::GridStatus::GlobalSettings => {
@@ -522,6 +1143,10 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                 options = options.push(iced::widget::value(t.rsplit(':').next().unwrap()).size(30));
                 // options = options.push(iced::widget::text(t).size(30));
                 for k in cat {
+                    // ids belong to a single game
+                    if k == "id" {
+                        continue;
+                    }
                     let i = mg.default_config.get(&k).unwrap(); //expect(&format!("{k}")[..]);
                                                                 // let s = k.split(':').collect::<Vec<_>>();
                     let (v, uses_default) =
@@ -531,7 +1156,19 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                             (&i.1, true)
                         };
                     let label = i.0.clone() + " : ";
-                    options = options.push(get_widget(v, label, k, uses_default))
+                    let verbs = (k == "wine:winetricks_verbs").then(|| v.as_strarr());
+                    let build = (k == "wine:wine_build").then(|| v.as_string());
+                    let dlls = (k == "wine:dll_overrides").then(|| v.as_strarr());
+                    options = options.push(get_widget(v, label, k, uses_default));
+                    if let Some(build) = build {
+                        options = options.push(wine_build_picker(mg, build));
+                    }
+                    if let Some(dlls) = dlls {
+                        options = options.push(dll_override_adder(mg, dlls));
+                    }
+                    if let Some(verbs) = verbs {
+                        options = options.push(winetricks_picker(mg, verbs));
+                    }
                 }
             }
 
@@ -568,7 +1205,7 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                     for k in cat {
                         let i = mg.default_config.get(&k).unwrap(); //expect(&format!("{k}")[..]);
                         let s = k.split(':').collect::<Vec<_>>();
-                        if s.len() == 1 || s[0] == runner {
+                        if (s.len() == 1 || s[0] == runner) && k != "id" {
                             let (v, uses_default) =
                                 if let Some(v) = mg.temp_settings.as_ref().unwrap().0.get(&k) {
                                     (v, false)
@@ -576,7 +1213,19 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                                     (&i.1, true)
                                 };
                             let label = i.0.clone() + " : ";
-                            options = options.push(get_widget(v, label, k, uses_default))
+                            let verbs = (k == "wine:winetricks_verbs").then(|| v.as_strarr());
+                            let build = (k == "wine:wine_build").then(|| v.as_string());
+                            let dlls = (k == "wine:dll_overrides").then(|| v.as_strarr());
+                            options = options.push(get_widget(v, label, k, uses_default));
+                            if let Some(build) = build {
+                                options = options.push(wine_build_picker(mg, build));
+                            }
+                            if let Some(dlls) = dlls {
+                                options = options.push(dll_override_adder(mg, dlls));
+                            }
+                            if let Some(verbs) = verbs {
+                                options = options.push(winetricks_picker(mg, verbs));
+                            }
                         }
                     }
                 }
@@ -595,32 +1244,143 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
             options.into()
         }
         crate::GridStatus::Logs => {
+            let processes = &mg.games[mg.selected.unwrap()].managed_processes;
+            let processes = if processes.is_empty() {
+                text("")
+            } else {
+                text(format!(
+                    "running processes : {}",
+                    processes
+                        .iter()
+                        .map(|(pid, name)| format!("{name} ({pid})"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            };
             /* crate::theme::widget::Scrollable::new( */
-            iced::widget::Container::new(
-                iced::widget::TextEditor::new(
-                    {
-                        //
-                        &mg.log
-                    }, //         if let Some(g) = mg.selected {
-                       //     &iced::widget::text_editor::Content::with_text(&mg.games[g].current_log)
-                       // } else {
-                       //     &iced::widget::text_editor::Content::new()
-                       // }
+            column![
+                processes,
+                iced::widget::Container::new(
+                    iced::widget::TextEditor::new(
+                        {
+                            //
+                            &mg.log
+                        }, //         if let Some(g) = mg.selected {
+                           //     &iced::widget::text_editor::Content::with_text(&mg.games[g].current_log)
+                           // } else {
+                           //     &iced::widget::text_editor::Content::new()
+                           // }
+                    )
+                    .on_action(Message::LogAction)
+                    .height(Length::Fill),
                 )
-                .on_action(Message::LogAction)
-                .height(Length::Fill),
-            )
-            .height(Length::Fill)
+                .height(Length::Fill)
+            ]
             .into()
         }
+        crate::GridStatus::Stats => {
+            let stats = mg.stats.clone();
+            let chart = |bars: Vec<crate::stats::Bar>, horizontal: bool| {
+                let height = if horizontal {
+                    bars.len() as f32 * 28.
+                } else {
+                    250.
+                };
+                iced::widget::canvas(crate::stats::BarChart {
+                    bars,
+                    horizontal,
+                    selected: mg.selected,
+                })
+                .width(Length::Fill)
+                .height(Length::Fixed(height))
+            };
+            column![
+                row![
+                    text("time played per "),
+                    iced::widget::pick_list(
+                        crate::stats::Period::ALL,
+                        Some(mg.stats_period),
+                        Message::StatsPeriodChanged
+                    ),
+                ]
+                .align_y(Alignment::Center),
+                text(format!(
+                    "total : {}    current streak : {} days    longest streak : {} days",
+                    crate::duration_to_string(stats.total),
+                    stats.current_streak,
+                    stats.longest_streak,
+                )),
+                chart(stats.timeline, false),
+                text("most played games"),
+                chart(crate::stats::top_games(&mg.games), true),
+                text("time played per runner"),
+                chart(stats.runners, true),
+            ]
+            .spacing(10)
+            .into()
+        }
+        crate::GridStatus::BrokenEntries => {
+            let mut entries = column![text(
+                "These files couldn't be loaded. Fix them and reload them to bring the games back."
+            )]
+            .spacing(15);
+            for (i, b) in mg.broken.iter().enumerate() {
+                entries = entries.push(
+                    column![
+                        text(b.path.to_string_lossy().into_owned()).size(20),
+                        text(b.error.clone()).style(text::danger),
+                        row![
+                            iced::widget::button(text("Open in editor"))
+                                .on_press(Message::OpenBrokenEntry(i)),
+                            iced::widget::button(text("Reset runner"))
+                                .on_press(Message::ResetBrokenRunner(i)),
+                            iced::widget::button(text("Remove box art"))
+                                .on_press(Message::RemoveBrokenBoxArt(i)),
+                            iced::widget::button(text("Reload"))
+                                .on_press(Message::ReloadBrokenEntry(i)),
+                        ]
+                        .spacing(10),
+                    ]
+                    .spacing(5),
+                );
+            }
+            entries.into()
+        }
     };
 
     let content = column![
         // row![game_viewer, global_settings]
         //     .spacing(10)
         top_bar.height(Length::Fixed(43.)), /* .into() */
+        if let crate::GridStatus::GamesGrid = mg.grid_status {
+            search_bar(mg)
+        } else {
+            iced::widget::Space::with_height(Length::Shrink).into()
+        },
         if let crate::GridStatus::Logs = mg.grid_status {
             ge
+        } else if mg.grid_status == crate::GridStatus::GamesGrid {
+            // the detail view scrolls its two halves separately
+            let library: iced::Element<'_, Message> =
+                if mg.library_view == crate::views::LibraryView::Detail {
+                    ge
+                } else {
+                    iced::widget::Container::new(
+                        iced::widget::scrollable(ge)
+                            .id(GRID_ID.clone())
+                            .on_scroll(|v| Message::GridScrolled(Some(v))),
+                    )
+                    .height(Length::Fill)
+                    .into()
+                };
+            if mg.sidebar_open {
+                row![sidebar(mg), library]
+                    .spacing(20)
+                    .height(Length::Fill)
+                    .into()
+            } else {
+                library
+            }
         } else {
             iced::widget::Container::new(iced::widget::scrollable(ge))
                 .height(Length::Fill)
@@ -728,29 +1488,74 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
                         .height(Length::FillPortion(1))
                     ]
                 })
-                .style(|theme: &Theme, _status| {
-                    let palette = theme.extended_palette();
-                    let color = palette.primary.strong.color;
-                    let text_color = palette.primary.strong.text;
-                    let foreground = theme.palette();
-
-                    iced_aw::widget::card::Style {
-                        border_color: color,
-                        head_background: color.into(),
-                        head_text_color: text_color,
-                        close_color: text_color,
-                        background: palette.background.base.color.into(),
-                        body_text_color: foreground.text,
-                        foot_text_color: foreground.text,
-                        ..iced_aw::widget::card::Style::default()
-                    }
-                })
+                .style(card_style)
                 .height(Length::FillPortion(10)),
                 iced::widget::Space::with_height(Length::FillPortion(1)),
             ]
             .into(),
         ])
         .into()
+    } else if let (true, Some(i)) = (mg.confirm_delete, mg.selected) {
+        iced::widget::stack(vec![
+            content.into(),
+            container(
+                iced_aw::Card::new(
+                    text("Delete game"),
+                    text(format!(
+                        "Move \"{}\" to the trash ? Its banner goes with it, its playtime \
+                         history is kept in case it is restored.",
+                        mg.games[i].name
+                    )),
+                )
+                .foot(
+                    row![
+                        iced::widget::Space::with_width(Length::Fill),
+                        iced::widget::button(text("Cancel")).on_press(Message::CancelDelete),
+                        iced::widget::button(text("Delete"))
+                            .style(button::danger)
+                            .on_press_maybe(
+                                (!mg.games[i].is_running).then_some(Message::ConfirmDelete)
+                            ),
+                    ]
+                    .spacing(10),
+                )
+                .max_width(500.)
+                .style(card_style),
+            )
+            .center(Length::Fill)
+            .into(),
+        ])
+        .into()
+    } else if let (Some(s), Some(i)) = (&mg.confirm_subcommand, mg.selected) {
+        let g = &mg.games[i];
+        let refused = mg.subcommand_refused.is_some();
+        let body = match &mg.subcommand_refused {
+            Some(e) => text(e).style(text::danger),
+            None => text(g.runner.confirm_subcommand(s).unwrap_or_default()),
+        };
+        iced::widget::stack(vec![
+            content.into(),
+            container(
+                iced_aw::Card::new(text(s.clone()), body)
+                    .foot(
+                        row![
+                            iced::widget::Space::with_width(Length::Fill),
+                            iced::widget::button(text("Cancel")).on_press(Message::CancelDelete),
+                            iced::widget::button(text("Confirm"))
+                                .style(button::danger)
+                                .on_press_maybe(
+                                    (!refused).then(|| Message::RunSubcommandSelected(s.clone()))
+                                ),
+                        ]
+                        .spacing(10),
+                    )
+                    .max_width(500.)
+                    .style(card_style),
+            )
+            .center(Length::Fill)
+            .into(),
+        ])
+        .into()
     } else {
         content.into()
     };
@@ -760,3 +1565,21 @@ pub fn get_view_widget(mg: &crate::MainGUI) -> iced::Element<'_, Message> {
         .center(iced::Length::Fill)
         .into()
 }
+
+fn card_style(theme: &Theme, _status: iced_aw::card::Status) -> iced_aw::widget::card::Style {
+    let palette = theme.extended_palette();
+    let color = palette.primary.strong.color;
+    let text_color = palette.primary.strong.text;
+    let foreground = theme.palette();
+
+    iced_aw::widget::card::Style {
+        border_color: color,
+        head_background: color.into(),
+        head_text_color: text_color,
+        close_color: text_color,
+        background: palette.background.base.color.into(),
+        body_text_color: foreground.text,
+        foot_text_color: foreground.text,
+        ..iced_aw::widget::card::Style::default()
+    }
+}
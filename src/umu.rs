@@ -11,7 +11,7 @@ pub struct UmuRunner {
 }
 
 impl Runner for UmuRunner {
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
         let mut envs = std::collections::HashMap::new();
 
         envs.insert("GAMEID".to_owned(), self.gameid.clone());
@@ -21,17 +21,17 @@ impl Runner for UmuRunner {
         envs.insert("PROTONPATH".to_owned(), self.path_to_proton.clone());
 
         let args = vec![self.path.clone()];
-        Command {
+        Ok(Command {
             program: self.path_to_umu.clone(),
             args,
             envs,
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec![]
     }
-    fn get_subcommand_command(&self, _command: String) -> Option<Command> {
-        None
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
+        Err(LaunchError::UnknownSubcommand(command))
     }
 }
@@ -0,0 +1,58 @@
+//! The layouts the library can be shown in, all sharing the selection of the games grid.
+
+use std::collections::HashMap;
+
+use crate::config::CValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LibraryView {
+    /// box arts
+    #[default]
+    Grid,
+    /// a table with a row per game, sorted by clicking its headers
+    List,
+    /// a list of names next to the box art and details of the selected game
+    Detail,
+}
+
+impl LibraryView {
+    pub const ALL: [LibraryView; 3] = [LibraryView::Grid, LibraryView::List, LibraryView::Detail];
+
+    /// the view saved in the global settings
+    pub fn from_config(cfg: &HashMap<String, (String, CValue)>) -> Self {
+        cfg.get("launcher:view")
+            .map(|a| a.1.as_string())
+            .and_then(|s| Self::ALL.into_iter().find(|a| a.to_string() == s))
+            .unwrap_or_default()
+    }
+
+    /// the value of the `launcher:view` setting
+    pub fn to_config(self) -> CValue {
+        CValue::OneOff(
+            Self::ALL.iter().map(|a| a.to_string()).collect(),
+            Self::ALL.iter().position(|a| *a == self).unwrap_or(0),
+        )
+    }
+}
+
+impl std::fmt::Display for LibraryView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Grid => write!(f, "grid"),
+            Self::List => write!(f, "list"),
+            Self::Detail => write!(f, "detail"),
+        }
+    }
+}
+
+/// the widths the box arts of the grid can be given, in logical pixels
+pub const COVER_WIDTHS: std::ops::RangeInclusive<f32> = 100.0..=400.0;
+
+/// the width of the box arts saved in the global settings
+pub fn cover_width_from_config(cfg: &HashMap<String, (String, CValue)>) -> f32 {
+    cfg.get("launcher:cover_width")
+        .and_then(|a| a.1.as_string().trim().parse::<f32>().ok())
+        .filter(|a| a.is_finite())
+        .unwrap_or(crate::IMAGE_WIDTH as f32)
+        .clamp(*COVER_WIDTHS.start(), *COVER_WIDTHS.end())
+}
@@ -9,30 +9,30 @@ pub struct Vita3kRunner {
 }
 
 impl Runner for Vita3kRunner {
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
         let mut args = vec!["-r".to_owned(), self.path.clone()];
         if self.fullscreen {
             args.insert(0, "--fullscreen".to_owned())
         }
-        Command {
+        Ok(Command {
             program: self.path_to_vita3k.clone(),
             args,
             envs: std::collections::HashMap::new(),
             cwd: None,
-        }
+        })
     }
     fn get_subcommands(&self) -> Vec<String> {
         vec!["vita3k".to_owned()]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "vita3k" => Some(Command {
+            "vita3k" => Ok(Command {
                 program: self.path_to_vita3k.clone(),
                 args: vec![],
                 envs: std::collections::HashMap::new(),
                 cwd: None,
             }),
-            _ => None,
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
 }
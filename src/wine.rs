@@ -1,12 +1,100 @@
+use std::io::BufRead;
+
+use crate::dll_overrides::Override;
+use crate::doctor::Problem;
 use crate::games::*;
+use crate::wine_builds::WineBuild;
+
+/// the architecture of a wineprefix, chosen when it is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    Win32,
+    Win64,
+}
+
+impl Arch {
+    /// the value of `WINEARCH`, as set in the `wine:arch` setting
+    pub fn from_config(s: &str) -> Self {
+        if s == "win32" {
+            Self::Win32
+        } else {
+            Self::Win64
+        }
+    }
+}
+
+impl std::fmt::Display for Arch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Win32 => write!(f, "win32"),
+            Self::Win64 => write!(f, "win64"),
+        }
+    }
+}
+
+/// the architecture of an initialised wineprefix, from the `#arch=` line at the top of its
+/// `system.reg`
+pub fn prefix_arch(prefix: &std::path::Path) -> Option<Arch> {
+    let file = std::fs::File::open(prefix.join("system.reg")).ok()?;
+    std::io::BufReader::new(file)
+        .lines()
+        .take(20)
+        .map_while(Result::ok)
+        .find_map(|l| match l.trim().strip_prefix("#arch=")? {
+            "win32" => Some(Arch::Win32),
+            "win64" => Some(Arch::Win64),
+            _ => None,
+        })
+}
+
+/// the wineprefix of a game : the custom one (`None` standing for `~/.wine`), or one in the
+/// `prefixes` folder of the data directory, either for this game only or shared by all games
+pub fn prefix_for(location: &str, custom: Option<String>, game_id: &str) -> Option<String> {
+    let dir = crate::DIRS.data_dir().join("prefixes");
+    let prefix = match location {
+        "per game" => dir.join(game_id),
+        "shared" => dir.join("shared"),
+        _ => return custom,
+    };
+    Some(prefix.to_string_lossy().into_owned())
+}
+
+/// stops the wineserver of `prefix` and moves it to the trash, printing what is done. Folders that
+/// don't look like a wineprefix are left untouched, in case the path was set to the wrong one.
+pub fn delete_prefix(prefix: &std::path::Path, wineserver: &str) -> Result<(), String> {
+    if !prefix.join("system.reg").is_file() || !prefix.join("drive_c").is_dir() {
+        return Err(format!(
+            "{prefix:?} doesn't look like a wineprefix, it was left untouched"
+        ));
+    }
+    println!("stopping the wineserver of {prefix:?}");
+    if let Err(e) = std::process::Command::new(wineserver)
+        .arg("-k")
+        .env("WINEPREFIX", prefix)
+        .status()
+    {
+        log::warn!("couldn't run {wineserver} : {e}");
+    }
+    println!("moving {prefix:?} to the trash");
+    crate::library::move_to_trash(prefix)
+        .map_err(|e| format!("couldn't move {prefix:?} to the trash : {e}"))?;
+    println!("done, it can be restored from the trash");
+    Ok(())
+}
 
 /// runner for Windows applications via Wine compatibility layer
 /// provides access to vkd3d, dxvk and dxvk_nvapi, among other.
 #[derive(Debug, Clone)]
 pub struct WineRunner {
     pub path: String,
+    /// the name of the wine build to use, `path_to_wine` being used when empty
+    pub wine_build: String,
+    /// the folders of `launcher:wine_build_dirs`
+    pub build_dirs: Vec<String>,
     pub path_to_wine: String,
     pub wineprefix: Option<String>,
+    /// the architecture of the prefix when it is created
+    pub arch: Arch,
     pub use_vkd3d: bool,
     pub vkd3d_path: Option<String>,
     pub use_dxvk: bool,
@@ -18,6 +106,11 @@ pub struct WineRunner {
     pub use_fsr: bool,
     pub fsr_strength: String,
     pub args: Vec<String>,
+    pub path_to_winetricks: String,
+    /// applied once to the prefix
+    pub winetricks_verbs: Vec<String>,
+    /// the overrides of the global settings, then those of the game
+    pub dll_overrides: Vec<Override>,
 }
 
 impl Runner for WineRunner {
@@ -27,31 +120,195 @@ impl Runner for WineRunner {
             "winecfg".to_owned(),
             "cmd".to_owned(),
             "pkill wineserver".to_owned(),
+            "initialise prefix".to_owned(),
+            "update prefix".to_owned(),
+            "delete prefix".to_owned(),
+            "apply winetricks verbs".to_owned(),
         ]
     }
-    fn get_subcommand_command(&self, command: String) -> Option<Command> {
+    fn setup_subcommand(&self) -> Option<String> {
+        let prefix = std::path::PathBuf::from(self.prefix());
+        // winetricks creates the prefix if needed
+        if !crate::winetricks::pending(&prefix, &self.winetricks_verbs).is_empty() {
+            Some("apply winetricks verbs".to_owned())
+        } else if !prefix.join("system.reg").is_file() {
+            Some("initialise prefix".to_owned())
+        } else {
+            None
+        }
+    }
+    fn info(&self) -> Vec<(String, String)> {
+        let prefix = std::path::PathBuf::from(self.prefix());
+        let mut out = vec![(
+            "wineprefix".to_owned(),
+            match prefix_arch(&prefix) {
+                Some(arch) => format!("{} ({arch})", prefix.display()),
+                None => format!("{} (not initialised)", prefix.display()),
+            },
+        )];
+        for component in ["dxvk", "vkd3d", "dxvk_nvapi"] {
+            if let Some(i) = crate::dlls::installed_component(&prefix, component) {
+                out.push((format!("installed {component}"), i.version()));
+            }
+        }
+        let pending = crate::winetricks::pending(&prefix, &self.winetricks_verbs);
+        if !pending.is_empty() {
+            out.push(("winetricks verbs to apply".to_owned(), pending.join(", ")));
+        }
+        let (overrides, conflicts) = self.merge_overrides(self.installed_overrides(&prefix));
+        if !overrides.is_empty() {
+            out.push((
+                "WINEDLLOVERRIDES".to_owned(),
+                crate::dll_overrides::to_env(&overrides),
+            ));
+        }
+        for c in conflicts {
+            out.push(("dll override conflict".to_owned(), c.to_string()));
+        }
+        out
+    }
+    fn get_subcommand_command(&self, command: String) -> Result<Command, LaunchError> {
         match &command[..] {
-            "Wine Control Panel" => Some(self.real_get_command(Some("control".to_owned()))),
-            "winecfg" => Some(self.real_get_command(Some("winecfg".to_owned()))),
-            "cmd" => Some(self.real_get_command(Some("cmd".to_owned()))),
-            "pkill wineserver" => Some(Command {
+            "Wine Control Panel" => self.real_get_command(Some("control".to_owned())),
+            "winecfg" => self.real_get_command(Some("winecfg".to_owned())),
+            "cmd" => self.real_get_command(Some("cmd".to_owned())),
+            "pkill wineserver" => Ok(Command {
                 program: "pkill".to_owned(),
                 cwd: None,
                 args: vec!["wineserver".to_owned()],
                 envs: std::collections::HashMap::new(),
             }),
-            _ => None,
+            "initialise prefix" => self.wineboot("--init"),
+            "apply winetricks verbs" => self.winetricks(),
+            // after a wine upgrade
+            "update prefix" => self.wineboot("--update"),
+            "delete prefix" => Ok(Command {
+                program: std::env::current_exe()
+                    .map_err(|e| LaunchError::Spawn("game_handler".to_owned(), e.to_string()))?
+                    .to_string_lossy()
+                    .into_owned(),
+                cwd: None,
+                args: vec![
+                    "delete-wineprefix".to_owned(),
+                    self.prefix(),
+                    self.wineserver(),
+                ],
+                envs: std::collections::HashMap::new(),
+            }),
+            _ => Err(LaunchError::UnknownSubcommand(command.to_owned())),
         }
     }
-    fn get_command(&self) -> Command {
+    fn get_command(&self) -> Result<Command, LaunchError> {
         self.real_get_command(None)
     }
+    fn wineprefix(&self) -> Option<String> {
+        Some(self.prefix())
+    }
+    fn confirm_subcommand(&self, subcommand: &str) -> Option<String> {
+        (subcommand == "delete prefix").then(|| {
+            format!(
+                "Move the wineprefix \"{}\" to the trash ? Everything installed in it goes with it.",
+                self.prefix()
+            )
+        })
+    }
+    fn prepare(&self) -> Result<(), LaunchError> {
+        let prefix = std::path::PathBuf::from(self.prefix());
+        // wine creates the prefix on its first run, the dlls are installed the next time
+        if !prefix.join("drive_c/windows/system32").is_dir() {
+            log::info!("{prefix:?} isn't initialised yet, the dlls will be installed later");
+            return Ok(());
+        }
+        let is_32bit = match prefix_arch(&prefix) {
+            Some(arch) => arch == Arch::Win32,
+            None => !prefix.join("drive_c/windows/syswow64").is_dir(),
+        };
+        for (component, enabled, folder, folder_32) in [
+            ("vkd3d", self.use_vkd3d, &self.vkd3d_path, "x86"),
+            ("dxvk", self.use_dxvk, &self.dxvk_path, "x32"),
+            (
+                "dxvk_nvapi",
+                self.use_dxvk_nvapi,
+                &self.dxvk_nvapi_path,
+                "x32",
+            ),
+        ] {
+            crate::dlls::sync(
+                &prefix, is_32bit, component, enabled, folder, "x64", folder_32,
+            )?;
+        }
+        Ok(())
+    }
+    fn diagnose(&self) -> Vec<Problem> {
+        let mut out = vec![];
+
+        if let Err(e) = self.build() {
+            out.push(Problem::error(e.to_string()));
+        }
+
+        let prefix = std::path::PathBuf::from(self.prefix());
+        if !prefix.is_dir() {
+            out.push(Problem::error(format!(
+                "wineprefix {prefix:?} doesn't exist"
+            )));
+        } else if !prefix.join("system.reg").is_file() {
+            out.push(Problem::error(format!(
+                "wineprefix {prefix:?} isn't initialised"
+            )));
+        } else if let Some(arch) = prefix_arch(&prefix).filter(|a| *a != self.arch) {
+            out.push(Problem::warning(format!(
+                "wineprefix {prefix:?} is {arch} while {} is set, it has to be deleted and \
+                 initialised again to change",
+                self.arch
+            )));
+        }
+
+        for (component, enabled, folder) in [
+            ("vkd3d", self.use_vkd3d, &self.vkd3d_path),
+            ("dxvk", self.use_dxvk, &self.dxvk_path),
+            ("dxvk_nvapi", self.use_dxvk_nvapi, &self.dxvk_nvapi_path),
+        ] {
+            match folder {
+                _ if !enabled => {}
+                Some(f) if std::path::Path::new(f).is_dir() => {}
+                Some(f) => out.push(Problem::error(format!(
+                    "{component} is enabled but \"{f}\" is not a folder"
+                ))),
+                None => out.push(Problem::error(format!(
+                    "{component} is enabled but no folder is set"
+                ))),
+            }
+            if let Some(i) = crate::dlls::installed_component(&prefix, component) {
+                if !std::path::Path::new(&i.source).is_dir() {
+                    out.push(Problem::warning(format!(
+                        "{component} was installed from \"{}\" which doesn't exist anymore",
+                        i.source
+                    )));
+                }
+            }
+        }
+
+        let pending = crate::winetricks::pending(&prefix, &self.winetricks_verbs);
+        if !pending.is_empty() {
+            out.push(Problem::warning(format!(
+                "winetricks verbs {} haven't been applied, see the \"apply winetricks verbs\" \
+                 subcommand",
+                pending.join(", ")
+            )));
+        }
+
+        let (_, conflicts) = self.merge_overrides(self.installed_overrides(&prefix));
+        for c in conflicts {
+            out.push(Problem::warning(format!("conflicting dll overrides : {c}")));
+        }
+
+        out
+    }
 }
 impl WineRunner {
-    fn real_get_command(&self, command_override: Option<String>) -> Command {
-        let mut dlloverrides = vec![];
-
-        let wineprefix = self.wineprefix.clone().unwrap_or(
+    /// the wineprefix actually used, which defaults to `~/.wine`
+    fn prefix(&self) -> String {
+        self.wineprefix.clone().unwrap_or(
             directories::BaseDirs::new()
                 .unwrap()
                 .home_dir()
@@ -59,174 +316,136 @@ impl WineRunner {
                 .to_str()
                 .unwrap()
                 .to_owned(),
-        );
+        )
+    }
 
-        let is_32bit =
-            !std::path::Path::new(&(wineprefix.clone() + "/drive_c/windows/syswow64")[..]).is_dir();
-        if self.use_vkd3d {
-            let mut dlls = vec![];
-            if !is_32bit {
-                for p in std::fs::read_dir(self.vkd3d_path.clone().unwrap() + "/x64").unwrap() {
-                    let from = p.unwrap().path();
-                    let to = wineprefix.clone()
-                        + "/drive_c/windows/system32/"
-                        + from.file_name().unwrap().to_str().unwrap();
-                    let success = std::fs::copy(from.clone(), to.clone());
-                    log::info!(
-                        "copied {:?} to {to} as part of vkd3d. This resulted in {:?}",
-                        from,
-                        success
-                    );
-                    dlls.push(
-                        from.with_extension("")
-                            .file_name()
-                            .unwrap()
-                            .to_str()
-                            .unwrap()
-                            .to_owned(),
-                    )
+    /// the overrides needed by the enabled components, as last installed in `prefix`
+    fn installed_overrides(&self, prefix: &std::path::Path) -> Vec<Override> {
+        let mut out = vec![];
+        for (component, enabled) in [
+            ("vkd3d", self.use_vkd3d),
+            ("dxvk", self.use_dxvk),
+            ("dxvk_nvapi", self.use_dxvk_nvapi),
+        ] {
+            let Some(i) = crate::dlls::installed_component(prefix, component).filter(|_| enabled)
+            else {
+                continue;
+            };
+            for f in i.files {
+                if let Some(dll) = std::path::Path::new(&f).file_stem() {
+                    out.push(Override {
+                        dll: dll.to_string_lossy().into_owned(),
+                        mode: crate::dll_overrides::Mode::Native,
+                        source: component.to_owned(),
+                    });
                 }
             }
-            for p in std::fs::read_dir(self.vkd3d_path.clone().unwrap() + "/x86").unwrap() {
-                let from = p.unwrap().path();
-                let to = wineprefix.clone()
-                    + if !is_32bit {
-                        "/drive_c/windows/syswow64/"
-                    } else {
-                        "/drive_c/windows/system32/"
-                    }
-                    + from.file_name().unwrap().to_str().unwrap();
-                let success = std::fs::copy(from.clone(), to.clone());
-                log::info!(
-                    "copied {:?} to {to} as part of vkd3d. This resulted in {:?}",
-                    from,
-                    success
-                );
-                dlls.push(
-                    from.with_extension("")
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                        .to_owned(),
-                )
-            }
-            dlls.sort();
-            dlls.dedup();
-            dlloverrides.push(dlls.join(",") + "=n");
         }
+        out
+    }
 
-        if self.use_dxvk {
-            let mut dlls = vec![];
-            if !is_32bit {
-                for p in std::fs::read_dir(self.dxvk_path.clone().unwrap() + "/x64").unwrap() {
-                    let from = p.unwrap().path();
-                    let to = wineprefix.clone()
-                        + "/drive_c/windows/system32/"
-                        + from.file_name().unwrap().to_str().unwrap();
-                    let success = std::fs::copy(from.clone(), to.clone());
-                    log::info!(
-                        "copied {:?} to {to} as part of vkd3d. This resulted in {:?}",
-                        from,
-                        success
-                    );
-                    dlls.push(
-                        from.with_extension("")
-                            .file_name()
-                            .unwrap()
-                            .to_str()
-                            .unwrap()
-                            .to_owned(),
-                    )
-                }
-            }
-            for p in std::fs::read_dir(self.dxvk_path.clone().unwrap() + "/x32").unwrap() {
-                let from = p.unwrap().path();
-                let to = wineprefix.clone()
-                    + if !is_32bit {
-                        "/drive_c/windows/syswow64/"
-                    } else {
-                        "/drive_c/windows/system32/"
-                    }
-                    + from.file_name().unwrap().to_str().unwrap();
-                let success = std::fs::copy(from.clone(), to.clone());
-                log::info!(
-                    "copied {:?} to {to} as part of vkd3d. This resulted in {:?}",
-                    from,
-                    success
-                );
-                dlls.push(
-                    from.with_extension("")
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                        .to_owned(),
-                )
-            }
-            dlls.sort();
-            dlls.dedup();
-            dlloverrides.push(dlls.join(",") + "=n");
+    /// the overrides of the components, replaced by those of the settings
+    fn merge_overrides(
+        &self,
+        components: Vec<Override>,
+    ) -> (Vec<Override>, Vec<crate::dll_overrides::Conflict>) {
+        let mut all = components;
+        all.extend(self.dll_overrides.clone());
+        crate::dll_overrides::merge(all)
+    }
+
+    /// the wine build chosen by name, or the one `path_to_wine` belongs to (`None` if it can't be
+    /// found, in which case it is run as is)
+    fn build(&self) -> Result<Option<WineBuild>, LaunchError> {
+        if self.wine_build.is_empty() {
+            return Ok(WineBuild::from_executable(&self.path_to_wine));
         }
+        let dirs = crate::wine_builds::search_dirs(&self.build_dirs);
+        crate::wine_builds::find(&dirs, &self.wine_build)
+            .map(Some)
+            .ok_or_else(|| {
+                LaunchError::MissingExecutable(format!("wine build {}", self.wine_build))
+            })
+    }
 
-        if self.use_dxvk_nvapi {
-            let mut dlls = vec![];
-            log::info!("{:?}", self.dxvk_nvapi_path);
-            if !is_32bit {
-                for p in std::fs::read_dir(self.dxvk_nvapi_path.clone().unwrap() + "/x64").unwrap()
-                {
-                    let from = p.unwrap().path();
-                    let to = wineprefix.clone()
-                        + "/drive_c/windows/system32/"
-                        + from.file_name().unwrap().to_str().unwrap();
-                    let success = std::fs::copy(from.clone(), to.clone());
-                    log::info!(
-                        "copied {:?} to {to} as part of vkd3d. This resulted in {:?}",
-                        from,
-                        success
-                    );
-                    dlls.push(
-                        from.with_extension("")
-                            .file_name()
-                            .unwrap()
-                            .to_str()
-                            .unwrap()
-                            .to_owned(),
-                    )
-                }
-            }
-            for p in std::fs::read_dir(self.dxvk_nvapi_path.clone().unwrap() + "/x32").unwrap() {
-                let from = p.unwrap().path();
-                let to = wineprefix.clone()
-                    + if !is_32bit {
-                        "/drive_c/windows/syswow64/"
-                    } else {
-                        "/drive_c/windows/system32/"
-                    }
-                    + from.file_name().unwrap().to_str().unwrap();
-                let success = std::fs::copy(from.clone(), to.clone());
-                log::info!(
-                    "copied {:?} to {to} as part of vkd3d. This resulted in {:?}",
-                    from,
-                    success
-                );
-                dlls.push(
-                    from.with_extension("")
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                        .to_owned(),
-                )
-            }
-            dlls.sort();
-            dlls.dedup();
-            dlloverrides.push(dlls.join(",") + "=n");
+    /// the wine executable, along with the variables its build needs
+    fn wine(&self) -> Result<(String, std::collections::HashMap<String, String>), LaunchError> {
+        Ok(match self.build()? {
+            // the path to wine is kept as is for the builds from the `PATH`
+            Some(b) if self.wine_build.is_empty() => (self.path_to_wine.clone(), b.envs()),
+            Some(b) => (b.wine().to_string_lossy().into_owned(), b.envs()),
+            None => (self.path_to_wine.clone(), std::collections::HashMap::new()),
+        })
+    }
+
+    /// `wineserver` next to the wine executable, or from the `PATH`
+    fn wineserver(&self) -> String {
+        let wine = self
+            .build()
+            .ok()
+            .flatten()
+            .map(|b| b.wine())
+            .unwrap_or(std::path::PathBuf::from(&self.path_to_wine));
+        wine.parent()
+            .map(|a| a.join("wineserver"))
+            .filter(|a| a.is_file())
+            .map(|a| a.to_string_lossy().into_owned())
+            .unwrap_or("wineserver".to_owned())
+    }
+
+    /// runs `wineboot` with `arg` in the prefix, creating it with the chosen architecture if needed
+    fn wineboot(&self, arg: &str) -> Result<Command, LaunchError> {
+        let prefix = self.prefix();
+        let (program, mut envs) = self.wine()?;
+        // wine refuses to start in an existing prefix of another architecture
+        if !std::path::Path::new(&prefix).join("system.reg").exists() {
+            envs.insert("WINEARCH".to_owned(), self.arch.to_string());
+        }
+        envs.insert("WINEPREFIX".to_owned(), prefix);
+        Ok(Command {
+            program,
+            cwd: None,
+            args: vec!["wineboot".to_owned(), arg.to_owned()],
+            envs,
+        })
+    }
+
+    /// applies the verbs missing from the prefix, or lists the installed ones if there are none
+    fn winetricks(&self) -> Result<Command, LaunchError> {
+        let prefix = self.prefix();
+        let pending =
+            crate::winetricks::pending(std::path::Path::new(&prefix), &self.winetricks_verbs);
+        let (wine, mut envs) = self.wine()?;
+        if !std::path::Path::new(&prefix).join("system.reg").exists() {
+            envs.insert("WINEARCH".to_owned(), self.arch.to_string());
         }
+        envs.insert("WINEPREFIX".to_owned(), prefix);
+        envs.insert("WINE".to_owned(), wine);
+        Ok(Command {
+            program: self.path_to_winetricks.clone(),
+            cwd: None,
+            args: if pending.is_empty() {
+                vec!["list-installed".to_owned()]
+            } else {
+                // unattended
+                std::iter::once("-q".to_owned()).chain(pending).collect()
+            },
+            envs,
+        })
+    }
+
+    fn real_get_command(&self, command_override: Option<String>) -> Result<Command, LaunchError> {
+        let wineprefix = self.prefix();
+        // the components are installed by `prepare`, and recorded in the prefix
+        let (dlloverrides, _) =
+            self.merge_overrides(self.installed_overrides(std::path::Path::new(&wineprefix)));
 
-        let mut envs = std::collections::HashMap::new();
+        let (program, mut envs) = self.wine()?;
         if !dlloverrides.is_empty() {
-            envs.insert("WINEDLLOVERRIDES".to_owned(), dlloverrides.join(";"));
+            envs.insert(
+                "WINEDLLOVERRIDES".to_owned(),
+                crate::dll_overrides::to_env(&dlloverrides),
+            );
         }
         if self.use_dxvk_nvapi {
             envs.insert("DXVK_ENABLE_NVAPI".to_owned(), "1".to_owned());
@@ -250,37 +469,8 @@ impl WineRunner {
         }
         envs.insert("WINE_LARGE_ADDRESS_AWARE".to_owned(), "1".to_owned());
 
-        if let Some(Some(p)) = std::path::Path::new(&self.path_to_wine)
-            .parent()
-            .map(|a| a.parent())
-        {
-            let mut ld_path = String::new();
-            let p1 = p.join("lib");
-            if p1.exists() {
-                if !ld_path.is_empty() {
-                    ld_path.push(';')
-                }
-                ld_path += p1.to_str().unwrap_or("");
-            }
-            let p2 = p.join("lib32");
-            if p2.exists() {
-                if !ld_path.is_empty() {
-                    ld_path.push(';')
-                }
-                ld_path += p2.to_str().unwrap_or("");
-            }
-            let p3 = p.join("lib64");
-            if p3.exists() {
-                if !ld_path.is_empty() {
-                    ld_path.push(';')
-                }
-                ld_path += p3.to_str().unwrap_or("");
-            }
-        }
-        // envs.insert("GST_PLUGIN_SYSTEM_PATH_1_0".to_owned(), )
-
-        Command {
-            program: self.path_to_wine.clone(),
+        Ok(Command {
+            program,
             args: if let Some(a) = command_override {
                 vec![a]
             } else {
@@ -294,6 +484,6 @@ impl WineRunner {
                 .map(|a| a.to_owned()), // .ok()
                                         // .map(|a| a.parent().map(|a| a.to_owned()))
                                         // .unwrap_or(None),
-        }
+        })
     }
 }
@@ -0,0 +1,193 @@
+//! The wine builds installed on the system, found in the folders set in the global settings along
+//! with the ones of lutris and of Steam's `compatibilitytools.d`, so that games can pick one by
+//! name and get the library paths it needs.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::CValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WineBuild {
+    /// the name of its folder, or `system` for the wine in the `PATH`
+    pub name: String,
+    /// the folder containing `bin/wine`
+    pub root: PathBuf,
+    /// the `lib`, `lib32` and `lib64` folders of the build
+    pub lib_dirs: Vec<PathBuf>,
+    /// whether it is laid out like Proton, with wine in `files` or `dist`
+    pub proton: bool,
+    /// the output of `wine --version`, once read
+    pub version: Option<String>,
+}
+
+impl WineBuild {
+    pub fn wine(&self) -> PathBuf {
+        self.root.join("bin/wine")
+    }
+
+    /// the build whose wine executable is `wine`, which is looked for in the `PATH` when it isn't
+    /// a path. Its libraries are only used when it looks like a build, with its dlls in
+    /// `lib*/wine`, rather than e.g. a wrapper script in `~/bin`.
+    pub fn from_executable(wine: &str) -> Option<Self> {
+        let wine = crate::games::find_executable(wine, None)?;
+        let root = wine.parent()?.parent()?.to_owned();
+        let mut libs = lib_dirs(&root);
+        if !libs.iter().any(|a| a.join("wine").is_dir()) {
+            libs.clear();
+        }
+        Some(Self {
+            name: root.file_name()?.to_string_lossy().into_owned(),
+            lib_dirs: libs,
+            root,
+            proton: false,
+            version: None,
+        })
+    }
+
+    /// whether the build is installed by the distribution, and so uses the system libraries
+    pub fn is_system(&self) -> bool {
+        self.root.starts_with("/usr")
+    }
+
+    /// the variables the build needs to find its own libraries, dlls and gstreamer plugins
+    pub fn envs(&self) -> HashMap<String, String> {
+        let mut out = HashMap::new();
+        if self.is_system() || self.lib_dirs.is_empty() {
+            return out;
+        }
+        // 64 bits first
+        let mut libs = self.lib_dirs.clone();
+        libs.sort_by_key(|a| !a.ends_with("lib64"));
+        let join = |dirs: Vec<PathBuf>| {
+            std::env::join_paths(dirs)
+                .ok()
+                .map(|a| a.to_string_lossy().into_owned())
+                .filter(|a| !a.is_empty())
+        };
+
+        let mut ld = libs.clone();
+        if let Some(existing) = std::env::var_os("LD_LIBRARY_PATH") {
+            ld.extend(std::env::split_paths(&existing));
+        }
+        if let Some(ld) = join(ld) {
+            out.insert("LD_LIBRARY_PATH".to_owned(), ld);
+        }
+        let dll = libs.iter().map(|a| a.join("wine")).filter(|a| a.is_dir());
+        if let Some(dll) = join(dll.collect()) {
+            out.insert("WINEDLLPATH".to_owned(), dll);
+        }
+        let gst = libs
+            .iter()
+            .map(|a| a.join("gstreamer-1.0"))
+            .filter(|a| a.is_dir());
+        if let Some(gst) = join(gst.collect()) {
+            out.insert("GST_PLUGIN_SYSTEM_PATH_1_0".to_owned(), gst);
+        }
+        out
+    }
+}
+
+fn lib_dirs(root: &Path) -> Vec<PathBuf> {
+    ["lib", "lib32", "lib64"]
+        .iter()
+        .map(|a| root.join(a))
+        .filter(|a| a.is_dir())
+        .collect()
+}
+
+/// the build in `folder`, either a plain wine or a Proton
+fn probe(folder: &Path) -> Option<WineBuild> {
+    let name = folder.file_name()?.to_string_lossy().into_owned();
+    let (root, proton) = if folder.join("bin/wine").is_file() {
+        (folder.to_owned(), false)
+    } else {
+        let root = ["files", "dist"]
+            .iter()
+            .map(|a| folder.join(a))
+            .find(|a| a.join("bin/wine").is_file())?;
+        (root, true)
+    };
+    Some(WineBuild {
+        name,
+        lib_dirs: lib_dirs(&root),
+        root,
+        proton,
+        version: None,
+    })
+}
+
+/// the folders searched for builds : the ones of `launcher:wine_build_dirs`, then those of lutris
+/// and Steam
+pub fn search_dirs(extra: &[String]) -> Vec<PathBuf> {
+    let mut out = extra
+        .iter()
+        .filter(|a| !a.is_empty())
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    if let Some(dirs) = directories::BaseDirs::new() {
+        out.push(dirs.data_dir().join("lutris/runners/wine"));
+        out.push(dirs.data_dir().join("Steam/compatibilitytools.d"));
+        out.push(dirs.home_dir().join(".steam/root/compatibilitytools.d"));
+    }
+    out
+}
+
+/// the builds in `dirs`, along with the one in the `PATH`. Their version isn't read.
+pub fn discover(dirs: &[PathBuf]) -> Vec<WineBuild> {
+    let mut out = vec![];
+    if let Some(mut system) = WineBuild::from_executable("wine") {
+        system.name = "system".to_owned();
+        out.push(system);
+    }
+    for d in dirs {
+        let Ok(entries) = std::fs::read_dir(d) else {
+            continue;
+        };
+        let mut builds = entries
+            .filter_map(|a| a.ok())
+            .filter_map(|a| probe(&a.path()))
+            .collect::<Vec<_>>();
+        builds.sort_by(|a, b| a.name.cmp(&b.name));
+        out.extend(builds);
+    }
+    // the same build may be found through several folders, e.g. Steam's symlinks
+    let mut seen = vec![];
+    out.retain(|b| {
+        let root = b.root.canonicalize().unwrap_or(b.root.clone());
+        let new = !seen.contains(&root);
+        seen.push(root);
+        new
+    });
+    out
+}
+
+/// the build called `name` in `dirs`
+pub fn find(dirs: &[PathBuf], name: &str) -> Option<WineBuild> {
+    discover(dirs).into_iter().find(|a| a.name == name)
+}
+
+/// reads the version of every build, which runs each of them
+pub fn read_versions(builds: &mut [WineBuild]) {
+    for b in builds {
+        b.version = std::process::Command::new(b.wine())
+            .arg("--version")
+            .envs(b.envs())
+            .output()
+            .ok()
+            .filter(|a| a.status.success())
+            .map(|a| String::from_utf8_lossy(&a.stdout).trim().to_owned());
+    }
+}
+
+/// the builds found with the global settings, with their version
+pub fn scan(cfg: &HashMap<String, (String, CValue)>) -> Vec<WineBuild> {
+    let dirs = search_dirs(
+        &cfg.get("launcher:wine_build_dirs")
+            .map(|a| a.1.as_strarr())
+            .unwrap_or_default(),
+    );
+    let mut builds = discover(&dirs);
+    read_versions(&mut builds);
+    builds
+}
@@ -0,0 +1,74 @@
+//! Winetricks verbs : the ones available, as listed by `winetricks list-all`, and the ones applied
+//! to a prefix, which winetricks records in `winetricks.log` inside of it.
+
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct Verb {
+    pub name: String,
+    /// e.g. `dlls` or `fonts`
+    pub category: String,
+    pub description: String,
+}
+
+/// parses the output of `winetricks list-all`, where each category starts with `===== name =====`
+pub fn parse_list(s: &str) -> Vec<Verb> {
+    let mut category = String::new();
+    let mut out = vec![];
+    for l in s.lines() {
+        if let Some(c) = l.strip_prefix("=====") {
+            category = c
+                .trim_matches(|a: char| a == '=' || a.is_whitespace())
+                .to_owned();
+        } else if let Some((name, description)) = l.trim().split_once(char::is_whitespace) {
+            out.push(Verb {
+                name: name.to_owned(),
+                category: category.clone(),
+                description: description.trim().to_owned(),
+            });
+        }
+    }
+    out
+}
+
+/// the verbs known to the winetricks at `program`
+pub async fn list_all(program: String) -> Result<Vec<Verb>, String> {
+    let output = tokio::process::Command::new(&program)
+        .arg("list-all")
+        .output()
+        .await
+        .map_err(|e| format!("couldn't run {program} : {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{program} list-all failed : {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// the verbs already applied to `prefix`
+pub fn applied(prefix: &Path) -> Vec<String> {
+    std::fs::read_to_string(prefix.join("winetricks.log"))
+        .map(|s| s.lines().map(|a| a.trim().to_owned()).collect())
+        .unwrap_or_default()
+}
+
+/// the verbs of `verbs` that haven't been applied to `prefix` yet
+pub fn pending(prefix: &Path, verbs: &[String]) -> Vec<String> {
+    let applied = applied(prefix);
+    verbs
+        .iter()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty() && !applied.iter().any(|b| b == a))
+        .map(|a| a.to_owned())
+        .collect()
+}
+
+/// the verbs whose name or description contains `search`, ignoring case
+pub fn search<'a>(verbs: &'a [Verb], search: &str) -> impl Iterator<Item = &'a Verb> {
+    let search = search.to_lowercase();
+    verbs.iter().filter(move |v| {
+        v.name.to_lowercase().contains(&search) || v.description.to_lowercase().contains(&search)
+    })
+}